@@ -1 +1,13 @@
 pub use eth2::types::*;
+
+/// SSZ decoding for fork-dependent types whose encoding doesn't identify the fork, decoding
+/// the variant named by the `Eth-Consensus-Version` header instead of guessing.
+pub trait SszDecodeByFork: Sized {
+    fn from_ssz_bytes_by_fork(bytes: &[u8], fork_name: ForkName) -> Result<Self, ssz::DecodeError>;
+}
+
+/// Signing domain for validator registrations, builder bids and cancellations, shared by the
+/// clients and servers so every call site agrees on it.
+pub fn compute_builder_domain(spec: &ChainSpec) -> Hash256 {
+    spec.get_builder_domain()
+}
@@ -1 +1,302 @@
 pub use beacon_api_types::*;
+use builder_bid::SignedBuilderBid;
+use serde::{Deserialize, Serialize};
+
+/// Optional proposer preferences passed to `get_header` as query parameters.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GetHeaderPreferences {
+    /// Minimum bid value in wei the proposer will accept, as a decimal string.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "quoted_u256_option"
+    )]
+    pub min_value: Option<Uint256>,
+    /// Maximum number of blobs the proposer wants the payload to carry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_blobs: Option<u64>,
+}
+
+/// Decimal wei for optional values, matching `serde_utils::quoted_u256`.
+mod quoted_u256_option {
+    use super::Uint256;
+    use serde::de::IntoDeserializer;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &Option<Uint256>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match value {
+            Some(value) => serde_utils::quoted_u256::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Uint256>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| serde_utils::quoted_u256::deserialize(value.into_deserializer()))
+            .transpose()
+    }
+}
+
+/// Fork-agnostic accessors for the fields of a [`SignedBuilderBid`].
+pub trait SignedBuilderBidExt {
+    fn value(&self) -> &Uint256;
+
+    fn pubkey(&self) -> &PublicKeyBytes;
+
+    fn block_hash(&self) -> ExecutionBlockHash;
+}
+
+impl<E: EthSpec> SignedBuilderBidExt for SignedBuilderBid<E> {
+    fn value(&self) -> &Uint256 {
+        self.message.value()
+    }
+
+    fn pubkey(&self) -> &PublicKeyBytes {
+        self.message.pubkey()
+    }
+
+    fn block_hash(&self) -> ExecutionBlockHash {
+        self.message.header().block_hash()
+    }
+}
+
+/// Fork-agnostic accessors for the payload revealed by `submit_blinded_block`.
+pub trait FullPayloadContentsExt {
+    fn block_hash(&self) -> ExecutionBlockHash;
+
+    /// Number of blobs revealed alongside the payload, zero for payloads without blobs.
+    fn blobs_len(&self) -> usize;
+
+    fn fork_name(&self) -> ForkName;
+}
+
+impl<E: EthSpec> FullPayloadContentsExt for FullPayloadContents<E> {
+    fn block_hash(&self) -> ExecutionBlockHash {
+        match self {
+            FullPayloadContents::Payload(payload) => payload.block_hash(),
+            FullPayloadContents::PayloadAndBlobs(payload_and_blobs) => {
+                payload_and_blobs.execution_payload.block_hash()
+            }
+        }
+    }
+
+    fn blobs_len(&self) -> usize {
+        match self {
+            FullPayloadContents::Payload(_) => 0,
+            FullPayloadContents::PayloadAndBlobs(payload_and_blobs) => {
+                payload_and_blobs.blobs_bundle.blobs.len()
+            }
+        }
+    }
+
+    fn fork_name(&self) -> ForkName {
+        match self {
+            FullPayloadContents::Payload(payload) => payload.fork_name(),
+            FullPayloadContents::PayloadAndBlobs(payload_and_blobs) => {
+                payload_and_blobs.execution_payload.fork_name()
+            }
+        }
+    }
+}
+
+/// Builds a validator registration and signs it over the builder domain.
+pub fn build_signed_registration(
+    fee_recipient: Address,
+    gas_limit: u64,
+    timestamp: u64,
+    pubkey: PublicKeyBytes,
+    signer: &SecretKey,
+    spec: &ChainSpec,
+) -> SignedValidatorRegistrationData {
+    let message = ValidatorRegistrationData {
+        fee_recipient,
+        gas_limit,
+        timestamp,
+        pubkey,
+    };
+    let signing_root = message.signing_root(compute_builder_domain(spec));
+    SignedValidatorRegistrationData {
+        message,
+        signature: signer.sign(signing_root),
+    }
+}
+
+/// Builds a builder bid for `header` and signs it over the builder domain, with the bid's
+/// pubkey taken from `signer`. `blob_kzg_commitments` is ignored before Deneb and
+/// `execution_requests` before Electra.
+///
+/// Returns `None` if `header` isn't a header for `fork_name`, or for forks without builder bids.
+pub fn build_signed_builder_bid<E: EthSpec>(
+    fork_name: ForkName,
+    header: ExecutionPayloadHeader<E>,
+    value: Uint256,
+    blob_kzg_commitments: KzgCommitments<E>,
+    execution_requests: ExecutionRequests<E>,
+    signer: &SecretKey,
+    spec: &ChainSpec,
+) -> Option<SignedBuilderBid<E>> {
+    let pubkey = signer.public_key().compress();
+    let message = match (fork_name, header) {
+        (ForkName::Bellatrix, ExecutionPayloadHeader::Bellatrix(header)) => {
+            builder_bid::BuilderBid::Bellatrix(builder_bid::BuilderBidBellatrix {
+                header,
+                value,
+                pubkey,
+            })
+        }
+        (ForkName::Capella, ExecutionPayloadHeader::Capella(header)) => {
+            builder_bid::BuilderBid::Capella(builder_bid::BuilderBidCapella {
+                header,
+                value,
+                pubkey,
+            })
+        }
+        (ForkName::Deneb, ExecutionPayloadHeader::Deneb(header)) => {
+            builder_bid::BuilderBid::Deneb(builder_bid::BuilderBidDeneb {
+                header,
+                blob_kzg_commitments,
+                value,
+                pubkey,
+            })
+        }
+        (ForkName::Electra, ExecutionPayloadHeader::Electra(header)) => {
+            builder_bid::BuilderBid::Electra(builder_bid::BuilderBidElectra {
+                header,
+                blob_kzg_commitments,
+                execution_requests,
+                value,
+                pubkey,
+            })
+        }
+        _ => return None,
+    };
+    let signature = signer.sign(message.signing_root(compute_builder_domain(spec)));
+    Some(SignedBuilderBid { message, signature })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ssz::Encode;
+
+    type E = MainnetEthSpec;
+
+    const FORKS: [ForkName; 3] = [ForkName::Capella, ForkName::Deneb, ForkName::Electra];
+
+    /// Asserts that `value` survives a JSON round trip through `ForkVersionDeserialize`.
+    fn assert_json_round_trip<T>(value: &T, fork_name: ForkName)
+    where
+        T: Serialize + ForkVersionDeserialize + PartialEq + std::fmt::Debug,
+    {
+        let json = serde_json::to_value(value).unwrap();
+        let decoded = T::deserialize_by_fork::<serde_json::Value>(json, fork_name)
+            .unwrap_or_else(|e| panic!("failed to decode {} JSON: {:?}", fork_name, e));
+        assert_eq!(&decoded, value);
+    }
+
+    fn header(fork_name: ForkName) -> ExecutionPayloadHeader<E> {
+        match fork_name {
+            ForkName::Capella => ExecutionPayloadHeader::Capella(Default::default()),
+            ForkName::Deneb => ExecutionPayloadHeader::Deneb(Default::default()),
+            ForkName::Electra => ExecutionPayloadHeader::Electra(Default::default()),
+            fork_name => panic!("no header fixture for fork {}", fork_name),
+        }
+    }
+
+    fn payload(fork_name: ForkName) -> FullPayloadContents<E> {
+        let execution_payload = match fork_name {
+            ForkName::Capella => ExecutionPayload::Capella(Default::default()),
+            ForkName::Deneb => ExecutionPayload::Deneb(Default::default()),
+            ForkName::Electra => ExecutionPayload::Electra(Default::default()),
+            fork_name => panic!("no payload fixture for fork {}", fork_name),
+        };
+        if matches!(fork_name, ForkName::Deneb | ForkName::Electra) {
+            FullPayloadContents::PayloadAndBlobs(ExecutionPayloadAndBlobs {
+                execution_payload,
+                blobs_bundle: BlobsBundle::default(),
+            })
+        } else {
+            FullPayloadContents::Payload(execution_payload)
+        }
+    }
+
+    #[test]
+    fn signed_builder_bid_round_trips() {
+        let spec = ChainSpec::mainnet();
+        let signer = SecretKey::random();
+        for fork_name in FORKS {
+            let bid = build_signed_builder_bid(
+                fork_name,
+                header(fork_name),
+                Uint256::from(1_000_000_000u64),
+                KzgCommitments::<E>::default(),
+                ExecutionRequests::<E>::default(),
+                &signer,
+                &spec,
+            )
+            .unwrap_or_else(|| panic!("no bid built for fork {}", fork_name));
+            assert_eq!(bid.pubkey(), &signer.public_key().compress());
+            assert!(bid.verify_signature(&spec));
+
+            assert_json_round_trip(&bid, fork_name);
+            let decoded =
+                SignedBuilderBid::<E>::from_ssz_bytes_by_fork(&bid.as_ssz_bytes(), fork_name)
+                    .unwrap_or_else(|e| panic!("failed to decode {} SSZ: {:?}", fork_name, e));
+            assert_eq!(decoded, bid);
+        }
+    }
+
+    #[test]
+    fn signed_builder_bid_requires_matching_header() {
+        let bid = build_signed_builder_bid(
+            ForkName::Deneb,
+            header(ForkName::Capella),
+            Uint256::from(1u64),
+            KzgCommitments::<E>::default(),
+            ExecutionRequests::<E>::default(),
+            &SecretKey::random(),
+            &ChainSpec::mainnet(),
+        );
+        assert!(bid.is_none());
+    }
+
+    #[test]
+    fn full_payload_contents_round_trips() {
+        for fork_name in FORKS {
+            let payload = payload(fork_name);
+            assert_eq!(payload.fork_name(), fork_name);
+
+            assert_json_round_trip(&payload, fork_name);
+            let decoded = FullPayloadContents::<E>::from_ssz_bytes_by_fork(
+                &payload.as_ssz_bytes(),
+                fork_name,
+            )
+            .unwrap_or_else(|e| panic!("failed to decode {} SSZ: {:?}", fork_name, e));
+            assert_eq!(decoded, payload);
+        }
+    }
+
+    #[test]
+    fn signed_blinded_beacon_block_round_trips() {
+        for fork_name in FORKS {
+            let spec = fork_name.make_genesis_spec(E::default_spec());
+            let block = SignedBlindedBeaconBlock::<E>::from_block(
+                BlindedBeaconBlock::empty(&spec),
+                Signature::empty(),
+            );
+            assert_eq!(block.fork_name(&spec).unwrap(), fork_name);
+
+            assert_json_round_trip(&block, fork_name);
+            let decoded =
+                SignedBlindedBeaconBlock::<E>::from_ssz_bytes(&block.as_ssz_bytes(), &spec)
+                    .unwrap_or_else(|e| panic!("failed to decode {} SSZ: {:?}", fork_name, e));
+            assert_eq!(decoded, block);
+        }
+    }
+}
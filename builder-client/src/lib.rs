@@ -1,9 +1,20 @@
+mod multi_relay;
+
 pub use builder_api_types::*;
 pub use builder_bid::SignedBuilderBid;
-pub use ethereum_apis_common::ErrorResponse;
+use ethereum_apis_common::CONSENSUS_VERSION_HEADER;
+pub use ethereum_apis_common::{ClientConfig, ContentType, ErrorResponse, NetworkConfig};
+pub use multi_relay::{MultiRelayBuilderClient, MultiRelayHeaders, RelayHeaderResult};
+use reqwest::header::CONTENT_TYPE;
 use reqwest::Client;
 use reqwest::Url;
 use serde::de::DeserializeOwned;
+use ssz::Encode;
+use std::sync::Arc;
+
+/// `User-Agent` sent unless overridden through [`ClientConfig::user_agent`].
+pub const DEFAULT_USER_AGENT: &str =
+    concat!("ethereum-apis-builder-client/", env!("CARGO_PKG_VERSION"));
 
 #[derive(Debug)]
 pub enum Error {
@@ -12,6 +23,76 @@ pub enum Error {
     ServerMessage(ErrorResponse),
     StatusCode(reqwest::StatusCode),
     InvalidUrl(Url),
+    /// The response body exceeded the configured maximum size.
+    ResponseTooLarge {
+        limit: usize,
+    },
+    /// The request didn't complete within the allotted time.
+    Timeout,
+    /// A bid wasn't signed by the builder pubkey it claims.
+    InvalidBidSignature,
+    /// The response is for a different fork than the one scheduled at the requested slot.
+    ForkMismatch {
+        requested: ForkName,
+        returned: ForkName,
+    },
+    BidBelowThreshold {
+        value: Uint256,
+        min_value: Uint256,
+    },
+    /// Failures from a chunked request, keyed by the index of the failed chunk.
+    ChunksFailed(Vec<(usize, Error)>),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Reqwest(e) => write!(f, "request failed: {}", e),
+            Error::InvalidJson(e, text) => write!(f, "invalid JSON response ({}): {}", e, text),
+            Error::ServerMessage(response) => {
+                write!(f, "server returned {}: {}", response.code, response.message)
+            }
+            Error::StatusCode(status) => write!(f, "unexpected status code {}", status),
+            Error::InvalidUrl(url) => write!(f, "invalid base URL {}", url),
+            Error::ResponseTooLarge { limit } => {
+                write!(f, "response body exceeds limit of {} bytes", limit)
+            }
+            Error::Timeout => write!(f, "request timed out"),
+            Error::InvalidBidSignature => write!(f, "bid signature does not match its pubkey"),
+            Error::ForkMismatch {
+                requested,
+                returned,
+            } => write!(
+                f,
+                "requested a {} header but the server returned {}",
+                requested, returned
+            ),
+            Error::BidBelowThreshold { value, min_value } => {
+                write!(f, "bid value {} is below minimum {}", value, min_value)
+            }
+            Error::ChunksFailed(failures) => {
+                write!(f, "{} chunk(s) failed", failures.len())?;
+                for (index, e) in failures {
+                    write!(f, "; chunk {}: {}", index, e)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Reqwest(e) => Some(e),
+            Error::InvalidJson(e, _) => Some(e),
+            // Only one error can be the source; the rest are listed in the message.
+            Error::ChunksFailed(failures) => failures
+                .first()
+                .map(|(_, e)| e as &(dyn std::error::Error + 'static)),
+            _ => None,
+        }
+    }
 }
 
 impl From<reqwest::Error> for Error {
@@ -24,14 +105,84 @@ impl From<reqwest::Error> for Error {
 pub struct BuilderClient {
     client: Client,
     base_url: Url,
+    network_config: Option<Arc<NetworkConfig>>,
+    max_response_size: Option<usize>,
+    verify_bids: bool,
 }
 
 impl BuilderClient {
     pub fn new(base_url: Url) -> Self {
+        let client = Self::build_client(&ClientConfig::default())
+            .expect("default client configuration is valid");
+        Self::with_client(base_url, client)
+    }
+
+    /// Creates a client that sends its requests through `client`.
+    pub fn with_client(base_url: Url, client: Client) -> Self {
         Self {
-            client: Client::new(),
+            client,
             base_url,
+            network_config: None,
+            max_response_size: None,
+            verify_bids: false,
+        }
+    }
+
+    /// Creates a client with its `User-Agent`, connection pool and response size limit
+    /// configured by `config`.
+    pub fn with_client_config(base_url: Url, config: &ClientConfig) -> Result<Self, Error> {
+        let mut client = Self::with_client(base_url, Self::build_client(config)?);
+        client.max_response_size = config.max_response_size;
+        Ok(client)
+    }
+
+    fn build_client(config: &ClientConfig) -> Result<Client, reqwest::Error> {
+        let user_agent = config.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT);
+        let mut builder = Client::builder()
+            .user_agent(user_agent)
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .pool_idle_timeout(config.pool_idle_timeout);
+        if config.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        builder.build()
+    }
+
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    pub fn with_network_config(mut self, network_config: NetworkConfig) -> Self {
+        self.network_config = Some(Arc::new(network_config));
+        self
+    }
+
+    pub fn network_config(&self) -> Option<&NetworkConfig> {
+        self.network_config.as_deref()
+    }
+
+    /// Checks the signature of every bid returned by [`Self::get_header`] against the builder
+    /// pubkey it carries, rejecting forged bids with [`Error::InvalidBidSignature`].
+    pub fn with_bid_verification(mut self, network_config: NetworkConfig) -> Self {
+        self.verify_bids = true;
+        self.with_network_config(network_config)
+    }
+
+    /// Reads the response body, failing with [`Error::ResponseTooLarge`] as soon as it exceeds
+    /// the configured maximum response size.
+    async fn read_text(&self, mut response: reqwest::Response) -> Result<String, Error> {
+        let Some(limit) = self.max_response_size else {
+            return Ok(response.text().await?);
+        };
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > limit {
+                return Err(Error::ResponseTooLarge { limit });
+            }
+            body.extend_from_slice(&chunk);
         }
+        Ok(String::from_utf8_lossy(&body).into_owned())
     }
 
     async fn build_response<T>(&self, response: reqwest::Response) -> Result<T, Error>
@@ -39,8 +190,15 @@ impl BuilderClient {
         T: DeserializeOwned,
     {
         let status = response.status();
-        let text = response.text().await?;
+        let text = self.read_text(response).await?;
+
+        Self::parse_response(status, text)
+    }
 
+    fn parse_response<T>(status: reqwest::StatusCode, text: String) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
         if status.is_success() {
             serde_json::from_str(&text).map_err(|e| Error::InvalidJson(e, text))
         } else {
@@ -53,17 +211,47 @@ impl BuilderClient {
     pub async fn register_validators(
         &self,
         registrations: &[SignedValidatorRegistrationData],
+        content_type: ContentType,
     ) -> Result<(), Error> {
         let mut url = self.base_url.clone();
         url.path_segments_mut()
             .map_err(|_| Error::InvalidUrl(self.base_url.clone()))?
             .extend(&["eth", "v1", "builder", "validators"]);
 
-        let response = self.client.post(url).json(registrations).send().await?;
+        let request = match content_type {
+            ContentType::Json => self.client.post(url).json(registrations),
+            ContentType::Ssz => self
+                .client
+                .post(url)
+                .header(CONTENT_TYPE, content_type.to_string())
+                .body(registrations.to_vec().as_ssz_bytes()),
+        };
+        let response = request.send().await?;
 
         self.build_response(response).await
     }
 
+    /// Registers validators in batches of at most `chunk_size`, continuing past failed batches.
+    pub async fn register_validators_chunked(
+        &self,
+        registrations: &[SignedValidatorRegistrationData],
+        chunk_size: usize,
+        content_type: ContentType,
+    ) -> Result<(), Error> {
+        let mut failures = Vec::new();
+        for (index, chunk) in registrations.chunks(chunk_size.max(1)).enumerate() {
+            if let Err(e) = self.register_validators(chunk, content_type).await {
+                failures.push((index, e));
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::ChunksFailed(failures))
+        }
+    }
+
     pub async fn submit_blinded_block<E: EthSpec>(
         &self,
         block: &SignedBlindedBeaconBlock<E>,
@@ -78,12 +266,16 @@ impl BuilderClient {
         self.build_response(response).await
     }
 
+    /// Fetches the builder's bid for `slot`, or `None` if it has none.
+    ///
+    /// The request is driven entirely by the returned future, so dropping it (e.g. once a
+    /// local block has won the race) aborts the request and closes its connection.
     pub async fn get_header<E: EthSpec>(
         &self,
         slot: Slot,
         parent_hash: ExecutionBlockHash,
         pubkey: &PublicKeyBytes,
-    ) -> Result<SignedBuilderBid<E>, Error> {
+    ) -> Result<Option<SignedBuilderBid<E>>, Error> {
         let mut url = self.base_url.clone();
         url.path_segments_mut()
             .map_err(|_| Error::InvalidUrl(self.base_url.clone()))?
@@ -98,8 +290,91 @@ impl BuilderClient {
             ]);
 
         let response = self.client.get(url).send().await?;
+        let status = response.status();
+        let header_fork = response
+            .headers()
+            .get(CONSENSUS_VERSION_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<ForkName>().ok());
+        let text = self.read_text(response).await?;
 
-        self.build_response(response).await
+        // The builder has no bid for this slot.
+        if status == reqwest::StatusCode::NO_CONTENT
+            || (status.is_success() && text.trim().is_empty())
+        {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Self::parse_response(status, text);
+        }
+
+        let expected_fork = self
+            .network_config
+            .as_deref()
+            .map(|config| config.spec.fork_name_at_slot::<E>(slot));
+        let bid = Self::parse_header_response(text, header_fork, expected_fork)?;
+        match self.network_config.as_deref() {
+            Some(config) if self.verify_bids && !bid.verify_signature(&config.spec) => {
+                Err(Error::InvalidBidSignature)
+            }
+            _ => Ok(Some(bid)),
+        }
+    }
+
+    /// Decodes a `get_header` response body using the fork named by the response's
+    /// consensus-version header, or failing that its `version` field.
+    fn parse_header_response<E: EthSpec>(
+        text: String,
+        header_fork: Option<ForkName>,
+        expected_fork: Option<ForkName>,
+    ) -> Result<SignedBuilderBid<E>, Error> {
+        let mut body: serde_json::Value = match serde_json::from_str(&text) {
+            Ok(body) => body,
+            Err(e) => return Err(Error::InvalidJson(e, text)),
+        };
+        let returned_fork = header_fork.or_else(|| {
+            body.get("version")
+                .and_then(|version| version.as_str())
+                .and_then(|version| version.parse().ok())
+        });
+        let data = body
+            .get_mut("data")
+            .map(serde_json::Value::take)
+            .unwrap_or_default();
+
+        match (expected_fork, returned_fork) {
+            (Some(requested), Some(returned)) if requested != returned => {
+                Err(Error::ForkMismatch {
+                    requested,
+                    returned,
+                })
+            }
+            (_, Some(fork_name)) => {
+                SignedBuilderBid::deserialize_by_fork::<serde_json::Value>(data, fork_name)
+                    .map_err(|e| Error::InvalidJson(e, text))
+            }
+            (_, None) => serde_json::from_value(data).map_err(|e| Error::InvalidJson(e, text)),
+        }
+    }
+
+    /// Fetches a header as in [`Self::get_header`], rejecting bids worth less than `min_value`
+    /// with [`Error::BidBelowThreshold`].
+    pub async fn get_header_with_min_value<E: EthSpec>(
+        &self,
+        slot: Slot,
+        parent_hash: ExecutionBlockHash,
+        pubkey: &PublicKeyBytes,
+        min_value: Uint256,
+    ) -> Result<Option<SignedBuilderBid<E>>, Error> {
+        let Some(bid) = self.get_header(slot, parent_hash, pubkey).await? else {
+            return Ok(None);
+        };
+
+        let value = *bid.value();
+        if value < min_value {
+            return Err(Error::BidBelowThreshold { value, min_value });
+        }
+        Ok(Some(bid))
     }
 
     pub async fn get_status(&self) -> Result<(), Error> {
@@ -0,0 +1,70 @@
+use crate::{BuilderClient, Error, SignedBuilderBid, SignedBuilderBidExt};
+use builder_api_types::{EthSpec, ExecutionBlockHash, PublicKeyBytes, Slot};
+use futures::future::join_all;
+use reqwest::Url;
+use std::time::Duration;
+
+/// Queries `get_header` on several relays at once and picks the most valuable bid.
+#[derive(Clone)]
+pub struct MultiRelayBuilderClient {
+    relays: Vec<BuilderClient>,
+    timeout: Duration,
+}
+
+/// The outcome of a single relay's `get_header` request.
+pub struct RelayHeaderResult<E: EthSpec> {
+    pub relay: Url,
+    pub result: Result<Option<SignedBuilderBid<E>>, Error>,
+}
+
+/// The per-relay results of [`MultiRelayBuilderClient::get_header`], in the order the relays
+/// were given.
+pub struct MultiRelayHeaders<E: EthSpec> {
+    pub results: Vec<RelayHeaderResult<E>>,
+}
+
+impl<E: EthSpec> MultiRelayHeaders<E> {
+    /// The highest-value bid returned by any relay, ignoring relays that failed or timed out.
+    pub fn best(&self) -> Option<&SignedBuilderBid<E>> {
+        self.results
+            .iter()
+            .filter_map(|relay| relay.result.as_ref().ok()?.as_ref())
+            .max_by_key(|bid| *bid.value())
+    }
+}
+
+impl MultiRelayBuilderClient {
+    /// Creates a client that gives each relay at most `timeout` to answer.
+    pub fn new(relays: Vec<BuilderClient>, timeout: Duration) -> Self {
+        Self { relays, timeout }
+    }
+
+    pub fn relays(&self) -> &[BuilderClient] {
+        &self.relays
+    }
+
+    /// Requests a header from every relay concurrently. Relays that haven't answered within
+    /// the timeout are reported as [`Error::Timeout`]. Dropping the returned future cancels
+    /// every outstanding request.
+    pub async fn get_header<E: EthSpec>(
+        &self,
+        slot: Slot,
+        parent_hash: ExecutionBlockHash,
+        pubkey: &PublicKeyBytes,
+    ) -> MultiRelayHeaders<E> {
+        let requests = self.relays.iter().map(|relay| async move {
+            let result =
+                tokio::time::timeout(self.timeout, relay.get_header(slot, parent_hash, pubkey))
+                    .await
+                    .unwrap_or(Err(Error::Timeout));
+            RelayHeaderResult {
+                relay: relay.base_url().clone(),
+                result,
+            }
+        });
+
+        MultiRelayHeaders {
+            results: join_all(requests).await,
+        }
+    }
+}
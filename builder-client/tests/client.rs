@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use builder_client::{BuilderClient, ExecutionBlockHash, MainnetEthSpec, PublicKeyBytes, Slot};
+use reqwest::Url;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpListener;
+
+type E = MainnetEthSpec;
+
+#[tokio::test]
+async fn dropping_get_header_closes_the_connection() {
+    // The listener accepts the request but never responds.
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+    let client = BuilderClient::new(url);
+
+    let request = tokio::spawn(async move {
+        client
+            .get_header::<E>(
+                Slot::new(1),
+                ExecutionBlockHash::zero(),
+                &PublicKeyBytes::empty(),
+            )
+            .await
+    });
+
+    let (mut socket, _) = listener.accept().await.unwrap();
+    let mut head = Vec::new();
+    let mut buf = [0; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = socket.read(&mut buf).await.unwrap();
+        assert_ne!(read, 0, "connection closed before the request was sent");
+        head.extend_from_slice(&buf[..read]);
+    }
+    assert!(head.starts_with(b"GET /eth/v1/builder/header/1/"));
+
+    request.abort();
+    assert!(request.await.unwrap_err().is_cancelled());
+
+    // The client closes the connection rather than leaving the request in flight.
+    let read = tokio::time::timeout(Duration::from_secs(5), socket.read(&mut buf))
+        .await
+        .expect("connection still open after dropping the request");
+    assert!(matches!(read, Ok(0) | Err(_)));
+}
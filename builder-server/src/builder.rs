@@ -1,11 +1,22 @@
 use async_trait::async_trait;
 use builder_api_types::{
     builder_bid::SignedBuilderBid, eth_spec::EthSpec, ExecutionBlockHash, ForkName,
-    FullPayloadContents, PublicKeyBytes, SignedBlindedBeaconBlock, SignedValidatorRegistrationData,
-    Slot,
+    FullPayloadContents, GetHeaderPreferences, PublicKeyBytes, SignedBlindedBeaconBlock,
+    SignedValidatorRegistrationData, Slot,
 };
 use ethereum_apis_common::ErrorResponse;
 
+/// Readiness reported by the builder status endpoint.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum BuilderStatus {
+    /// The builder can serve bids.
+    #[default]
+    Ready,
+    /// The builder is running but can't serve bids yet, e.g. because its execution engine is
+    /// unreachable.
+    NotReady,
+}
+
 #[async_trait]
 pub trait Builder<E: EthSpec> {
     async fn register_validators(
@@ -23,7 +34,44 @@ pub trait Builder<E: EthSpec> {
         slot: Slot,
         parent_hash: ExecutionBlockHash,
         pubkey: PublicKeyBytes,
+        preferences: GetHeaderPreferences,
     ) -> Result<SignedBuilderBid<E>, ErrorResponse>;
 
+    /// Returns the full payload previously bid at `slot` with `block_hash`, independently of
+    /// `submit_blinded_block`. Returns a `404` if the payload is unknown.
+    async fn get_payload(
+        &self,
+        slot: Slot,
+        block_hash: ExecutionBlockHash,
+    ) -> Result<FullPayloadContents<E>, ErrorResponse> {
+        Err(ErrorResponse {
+            code: 404,
+            message: format!(
+                "no payload with block hash {:?} at slot {}",
+                block_hash, slot
+            ),
+            stacktraces: None,
+        })
+    }
+
     fn fork_name_at_slot(&self, slot: Slot) -> ForkName;
+
+    /// Readiness served by the status endpoint. A builder that is up always answers the
+    /// endpoint, so a `503` distinguishes "alive but not ready" from being down.
+    async fn status(&self) -> BuilderStatus {
+        BuilderStatus::Ready
+    }
+
+    /// Returns the validator registrations currently held by the builder.
+    ///
+    /// Only served by the router when the `debug` feature is enabled.
+    async fn get_registered_validators(
+        &self,
+    ) -> Result<Vec<SignedValidatorRegistrationData>, ErrorResponse> {
+        Err(ErrorResponse {
+            code: 404,
+            message: "registered validators are not exposed by this builder".to_string(),
+            stacktraces: None,
+        })
+    }
 }
@@ -1,19 +1,23 @@
 use axum::{
     body::Body,
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{header::ACCEPT, HeaderMap, StatusCode},
+    middleware,
     response::Response,
     routing::{get, post},
     Json, Router,
 };
 use builder_api_types::{
     eth_spec::EthSpec, fork_versioned_response::EmptyMetadata, ExecutionBlockHash,
-    ForkVersionedResponse, FullPayloadContents, PublicKeyBytes, SignedBlindedBeaconBlock,
-    SignedValidatorRegistrationData, Slot,
+    ForkVersionedResponse, FullPayloadContentsExt, GetHeaderPreferences, PublicKeyBytes,
+    SignedBlindedBeaconBlock, SignedValidatorRegistrationData, Slot,
+};
+use ethereum_apis_common::{
+    build_response, build_ssz_response, method_not_allowed, negotiate_content_type, not_found,
+    ContentType, ErrorResponse, JsonOrSsz, SszEnvelope,
 };
-use ethereum_apis_common::build_response;
 
-use crate::builder::Builder;
+use crate::builder::{Builder, BuilderStatus};
 
 pub fn new<I, A, E>(api_impl: I) -> Router
 where
@@ -21,7 +25,7 @@ where
     I: AsRef<A> + Clone + Send + Sync + 'static,
     A: Builder<E> + 'static,
 {
-    Router::new()
+    let router = Router::new()
         .route(
             "/eth/v1/builder/validators",
             post(register_validators::<I, A, E>),
@@ -30,17 +34,31 @@ where
             "/eth/v1/builder/blinded_blocks",
             post(submit_blinded_block::<I, A, E>),
         )
-        .route("/eth/v1/builder/status", get(get_status))
+        .route("/eth/v1/builder/status", get(get_status::<I, A, E>))
+        .route(
+            "/eth/v1/builder/payload/:slot/:block_hash",
+            get(get_payload::<I, A, E>),
+        )
         .route(
             "/eth/v1/builder/header/:slot/:parent_hash/:pubkey",
             get(get_header::<I, A, E>),
-        )
+        );
+
+    #[cfg(feature = "debug")]
+    let router = router.route(
+        "/debug/validators",
+        get(get_registered_validators::<I, A, E>),
+    );
+
+    router
+        .fallback(not_found)
+        .layer(middleware::map_response(method_not_allowed))
         .with_state(api_impl)
 }
 
 async fn register_validators<I, A, E>(
     State(api_impl): State<I>,
-    Json(registrations): Json<Vec<SignedValidatorRegistrationData>>,
+    JsonOrSsz(registrations): JsonOrSsz<Vec<SignedValidatorRegistrationData>>,
 ) -> Result<Response<Body>, StatusCode>
 where
     E: EthSpec,
@@ -53,6 +71,7 @@ where
 
 async fn submit_blinded_block<I, A, E>(
     State(api_impl): State<I>,
+    headers: HeaderMap,
     Json(block): Json<SignedBlindedBeaconBlock<E>>,
 ) -> Result<Response<Body>, StatusCode>
 where
@@ -60,47 +79,180 @@ where
     I: AsRef<A> + Send + Sync,
     A: Builder<E>,
 {
+    // The block is always submitted as JSON, so JSON is preferred unless the client asks for
+    // an SSZ payload.
+    let accept = headers
+        .get(ACCEPT)
+        .map(|value| value.to_str().unwrap_or_default());
+    let response_type = negotiate_content_type(accept, &[ContentType::Json, ContentType::Ssz])
+        .ok_or(StatusCode::NOT_ACCEPTABLE)?;
+
     let res = api_impl
         .as_ref()
         .submit_blinded_block(block)
         .await
-        .map(|payload| {
-            let fork_name = match &payload {
-                FullPayloadContents::Payload(payload) => payload.fork_name(),
-                FullPayloadContents::PayloadAndBlobs(payload_and_blobs) => {
-                    payload_and_blobs.execution_payload.fork_name()
-                }
-            };
-            ForkVersionedResponse {
-                version: Some(fork_name),
+        .inspect(|payload| {
+            tracing::debug!(
+                block_hash = %payload.block_hash(),
+                blobs = payload.blobs_len(),
+                "Revealed payload"
+            );
+        });
+
+    match (response_type, res) {
+        (ContentType::Ssz, Ok(payload)) => {
+            let fork_name = payload.fork_name();
+            build_ssz_response(Ok(payload), fork_name, SszEnvelope::None).await
+        }
+        (_, res) => {
+            let res = res.map(|payload| ForkVersionedResponse {
+                version: Some(payload.fork_name()),
                 metadata: EmptyMetadata {},
                 data: payload,
-            }
+            });
+            build_response(res).await
+        }
+    }
+}
+
+async fn get_payload<I, A, E>(
+    State(api_impl): State<I>,
+    Path((slot, block_hash)): Path<(String, String)>,
+) -> Result<Response<Body>, StatusCode>
+where
+    E: EthSpec,
+    I: AsRef<A> + Send + Sync,
+    A: Builder<E>,
+{
+    let (slot, block_hash) = match parse_payload_path(&slot, &block_hash) {
+        Ok(path) => path,
+        Err(e) => return build_response(Err::<(), _>(e)).await,
+    };
+
+    let res = api_impl
+        .as_ref()
+        .get_payload(slot, block_hash)
+        .await
+        .map(|payload| ForkVersionedResponse {
+            version: Some(payload.fork_name()),
+            metadata: EmptyMetadata {},
+            data: payload,
         });
     build_response(res).await
 }
 
-async fn get_status() -> StatusCode {
-    StatusCode::OK
+async fn get_status<I, A, E>(State(api_impl): State<I>) -> StatusCode
+where
+    E: EthSpec,
+    I: AsRef<A> + Send + Sync,
+    A: Builder<E>,
+{
+    match api_impl.as_ref().status().await {
+        BuilderStatus::Ready => StatusCode::OK,
+        BuilderStatus::NotReady => StatusCode::SERVICE_UNAVAILABLE,
+    }
 }
 
 async fn get_header<I, A, E>(
     State(api_impl): State<I>,
-    Path((slot, parent_hash, pubkey)): Path<(Slot, ExecutionBlockHash, PublicKeyBytes)>,
+    headers: HeaderMap,
+    Path((slot, parent_hash, pubkey)): Path<(String, String, String)>,
+    Query(preferences): Query<GetHeaderPreferences>,
 ) -> Result<Response<Body>, StatusCode>
 where
     E: EthSpec,
     I: AsRef<A> + Send + Sync,
     A: Builder<E>,
 {
+    let accept = headers
+        .get(ACCEPT)
+        .map(|value| value.to_str().unwrap_or_default());
+    let response_type = negotiate_content_type(accept, &[ContentType::Json, ContentType::Ssz])
+        .ok_or(StatusCode::NOT_ACCEPTABLE)?;
+
+    let (slot, parent_hash, pubkey) = match parse_header_path(&slot, &parent_hash, &pubkey) {
+        Ok(path) => path,
+        Err(e) => return build_response(Err::<(), _>(e)).await,
+    };
+
+    let fork_name = api_impl.as_ref().fork_name_at_slot(slot);
     let res = api_impl
         .as_ref()
-        .get_header(slot, parent_hash, pubkey)
-        .await
-        .map(|signed_bid| ForkVersionedResponse {
-            version: Some(api_impl.as_ref().fork_name_at_slot(slot)),
-            metadata: EmptyMetadata {},
-            data: signed_bid,
-        });
+        .get_header(slot, parent_hash, pubkey, preferences)
+        .await;
+
+    match response_type {
+        ContentType::Ssz => build_ssz_response(res, fork_name, SszEnvelope::None).await,
+        ContentType::Json => {
+            let res = res.map(|signed_bid| ForkVersionedResponse {
+                version: Some(fork_name),
+                metadata: EmptyMetadata {},
+                data: signed_bid,
+            });
+            build_response(res).await
+        }
+    }
+}
+
+#[cfg(feature = "debug")]
+async fn get_registered_validators<I, A, E>(
+    State(api_impl): State<I>,
+) -> Result<Response<Body>, StatusCode>
+where
+    E: EthSpec,
+    I: AsRef<A> + Send + Sync,
+    A: Builder<E>,
+{
+    let res = api_impl.as_ref().get_registered_validators().await;
     build_response(res).await
 }
+
+fn bad_request(message: String) -> ErrorResponse {
+    ErrorResponse {
+        code: StatusCode::BAD_REQUEST.as_u16(),
+        message,
+        stacktraces: None,
+    }
+}
+
+fn parse_slot(slot: &str) -> Result<Slot, ErrorResponse> {
+    slot.parse::<u64>()
+        .map(Slot::new)
+        .map_err(|_| bad_request(format!("slot {} is not a valid integer", slot)))
+}
+
+/// Parses the `get_header` path segments, naming the offending segment on failure.
+fn parse_header_path(
+    slot: &str,
+    parent_hash: &str,
+    pubkey: &str,
+) -> Result<(Slot, ExecutionBlockHash, PublicKeyBytes), ErrorResponse> {
+    let slot = parse_slot(slot)?;
+    let parent_hash = parent_hash.parse::<ExecutionBlockHash>().map_err(|_| {
+        bad_request(format!(
+            "parent_hash {} is not a valid 32-byte hex hash",
+            parent_hash
+        ))
+    })?;
+    let pubkey = pubkey
+        .parse::<PublicKeyBytes>()
+        .map_err(|_| bad_request(format!("pubkey {} is not a valid 48-byte BLS key", pubkey)))?;
+
+    Ok((slot, parent_hash, pubkey))
+}
+
+/// Parses the `get_payload` path segments, naming the offending segment on failure.
+fn parse_payload_path(
+    slot: &str,
+    block_hash: &str,
+) -> Result<(Slot, ExecutionBlockHash), ErrorResponse> {
+    let slot = parse_slot(slot)?;
+    let block_hash = block_hash.parse::<ExecutionBlockHash>().map_err(|_| {
+        bad_request(format!(
+            "block_hash {} is not a valid 32-byte hex hash",
+            block_hash
+        ))
+    })?;
+
+    Ok((slot, block_hash))
+}
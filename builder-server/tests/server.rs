@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::body::{to_bytes, Body};
+use axum::Router;
+use builder_api_types::{
+    build_signed_builder_bid, builder_bid::SignedBuilderBid, eth_spec::EthSpec, ExecutionBlockHash,
+    ExecutionPayloadHeader, ForkName, ForkVersionDeserialize, FullPayloadContents,
+    GetHeaderPreferences, KzgCommitments, MainnetEthSpec, PublicKeyBytes, SecretKey,
+    SignedBlindedBeaconBlock, SignedValidatorRegistrationData, Slot, SszDecodeByFork, Uint256,
+};
+use builder_server::builder::Builder;
+use ethereum_apis_common::ErrorResponse;
+use http::{
+    header::{ACCEPT, CONTENT_TYPE},
+    Request, StatusCode,
+};
+use ssz::Encode;
+use tower::ServiceExt;
+
+type E = MainnetEthSpec;
+
+struct TestBuilder {
+    bid: SignedBuilderBid<E>,
+}
+
+#[async_trait]
+impl Builder<E> for TestBuilder {
+    async fn register_validators(
+        &self,
+        _registrations: Vec<SignedValidatorRegistrationData>,
+    ) -> Result<(), ErrorResponse> {
+        Ok(())
+    }
+
+    async fn submit_blinded_block(
+        &self,
+        _block: SignedBlindedBeaconBlock<E>,
+    ) -> Result<FullPayloadContents<E>, ErrorResponse> {
+        Err(ErrorResponse {
+            code: 404,
+            message: "no payloads".to_string(),
+            stacktraces: None,
+        })
+    }
+
+    async fn get_header(
+        &self,
+        _slot: Slot,
+        _parent_hash: ExecutionBlockHash,
+        _pubkey: PublicKeyBytes,
+        _preferences: GetHeaderPreferences,
+    ) -> Result<SignedBuilderBid<E>, ErrorResponse> {
+        Ok(self.bid.clone())
+    }
+
+    fn fork_name_at_slot(&self, _slot: Slot) -> ForkName {
+        ForkName::Deneb
+    }
+}
+
+fn test_builder() -> Arc<TestBuilder> {
+    let bid = build_signed_builder_bid(
+        ForkName::Deneb,
+        ExecutionPayloadHeader::Deneb(Default::default()),
+        Uint256::from(1_000_000_000u64),
+        KzgCommitments::<E>::default(),
+        ExecutionRequests::<E>::default(),
+        &SecretKey::random(),
+        &E::default_spec(),
+    )
+    .unwrap();
+    Arc::new(TestBuilder { bid })
+}
+
+fn router(builder: Arc<TestBuilder>) -> Router {
+    builder_server::server::new::<_, TestBuilder, E>(builder)
+}
+
+fn header_path() -> String {
+    format!(
+        "/eth/v1/builder/header/1/0x{}/0x{}",
+        "11".repeat(32),
+        "22".repeat(48)
+    )
+}
+
+async fn get_header(builder: Arc<TestBuilder>, accept: &str) -> http::Response<Body> {
+    router(builder)
+        .oneshot(
+            Request::get(header_path())
+                .header(ACCEPT, accept)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn get_header_prefers_weighted_json() {
+    let builder = test_builder();
+    let response = get_header(
+        builder.clone(),
+        "application/json;q=0.9, application/octet-stream;q=0.5",
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let served = SignedBuilderBid::<E>::deserialize_by_fork::<serde_json::Value>(
+        json["data"].clone(),
+        ForkName::Deneb,
+    )
+    .unwrap();
+    assert_eq!(json["version"], "deneb");
+    assert_eq!(served.data, builder.bid);
+}
+
+#[tokio::test]
+async fn get_header_prefers_weighted_ssz() {
+    let builder = test_builder();
+    let response = get_header(
+        builder.clone(),
+        "application/json;q=0.5, application/octet-stream;q=0.9",
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/octet-stream");
+    assert_eq!(response.headers()["Eth-Consensus-Version"], "deneb");
+
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(body, builder.bid.as_ssz_bytes());
+    let served = SignedBuilderBid::<E>::from_ssz_bytes_by_fork(&body, ForkName::Deneb).unwrap();
+    assert_eq!(served, builder.bid);
+}
@@ -1,18 +1,29 @@
 use axum::{
     async_trait,
     body::Body,
-    extract::{FromRequest, Request},
+    extract::{rejection::BytesRejection, FromRequest, Request},
     response::{IntoResponse, Response},
+    Router,
+};
+use beacon_api_types::{
+    compute_builder_domain, ChainSpec, Domain, Epoch, ForkName, ForkVersionDeserialize, Hash256,
+    SszDecodeByFork,
 };
-use beacon_api_types::ForkVersionDeserialize;
 use bytes::Bytes;
-use flate2::read::GzDecoder;
-use http::header::CONTENT_ENCODING;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use http::header::{ALLOW, CONTENT_ENCODING};
 use http::{header::CONTENT_TYPE, HeaderValue, StatusCode};
 use serde::{Deserialize, Serialize};
+use ssz_derive::Encode;
 use std::io::Read;
+use std::time::Duration;
+use tower_http::limit::RequestBodyLimitLayer;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::timeout::TimeoutLayer;
 use tracing::error;
 
+pub use tower_http::cors::CorsLayer;
+
 pub const CONSENSUS_VERSION_HEADER: &'static str = "Eth-Consensus-Version";
 
 pub async fn build_response<T>(
@@ -89,6 +100,189 @@ where
     resp
 }
 
+/// How an SSZ response body conveys the fork of its payload.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SszEnvelope {
+    /// The bare SSZ bytes of the payload, with the fork given only by the
+    /// `Eth-Consensus-Version` header. This is what the beacon and builder API specs define and
+    /// should be used for all spec endpoints.
+    #[default]
+    None,
+    /// The payload wrapped in an [`SszForkVersionedResponse`], for consumers that store or
+    /// forward response bodies without their headers.
+    ForkVersioned,
+}
+
+/// SSZ counterpart of the JSON `ForkVersionedResponse`, embedding the fork name in the body.
+#[derive(Debug, Clone, PartialEq, Encode)]
+pub struct SszForkVersionedResponse<T: ssz::Encode> {
+    /// UTF-8 encoded fork name, e.g. `deneb`.
+    pub version: Vec<u8>,
+    pub data: T,
+}
+
+/// Decoding is implemented by hand so that encoding doesn't require `T: Decode`, which
+/// fork-dependent types such as builder bids don't implement.
+impl<T: ssz::Encode + ssz::Decode> ssz::Decode for SszForkVersionedResponse<T> {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, ssz::DecodeError> {
+        let mut builder = ssz::SszDecoderBuilder::new(bytes);
+        builder.register_type::<Vec<u8>>()?;
+        builder.register_type::<T>()?;
+        let mut decoder = builder.build()?;
+        Ok(Self {
+            version: decoder.decode_next()?,
+            data: decoder.decode_next()?,
+        })
+    }
+}
+
+impl<T: ssz::Encode> SszForkVersionedResponse<T> {
+    pub fn new(fork_name: ForkName, data: T) -> Self {
+        Self {
+            version: fork_name.to_string().into_bytes(),
+            data,
+        }
+    }
+
+    pub fn fork_name(&self) -> Option<ForkName> {
+        std::str::from_utf8(&self.version).ok()?.parse().ok()
+    }
+}
+
+/// Builds an SSZ response for `result`, setting the `Eth-Consensus-Version` header to
+/// `fork_name`. Errors are always returned as JSON.
+pub async fn build_ssz_response<T>(
+    result: Result<T, ErrorResponse>,
+    fork_name: ForkName,
+    envelope: SszEnvelope,
+) -> Result<Response<Body>, StatusCode>
+where
+    T: ssz::Encode + Send + 'static,
+{
+    let body = match result {
+        Ok(body) => body,
+        Err(e) => return build_response(Err::<(), _>(e)).await,
+    };
+
+    encode_ssz_response(
+        move || match envelope {
+            SszEnvelope::None => ssz::Encode::as_ssz_bytes(&body),
+            SszEnvelope::ForkVersioned => {
+                ssz::Encode::as_ssz_bytes(&SszForkVersionedResponse::new(fork_name, body))
+            }
+        },
+        Some(fork_name),
+    )
+    .await
+}
+
+/// Builds an SSZ response for `result` without an `Eth-Consensus-Version` header, for bodies
+/// whose encoding doesn't depend on the fork. Errors are always returned as JSON.
+pub async fn build_unversioned_ssz_response<T>(
+    result: Result<T, ErrorResponse>,
+) -> Result<Response<Body>, StatusCode>
+where
+    T: ssz::Encode + Send + 'static,
+{
+    let body = match result {
+        Ok(body) => body,
+        Err(e) => return build_response(Err::<(), _>(e)).await,
+    };
+
+    encode_ssz_response(move || ssz::Encode::as_ssz_bytes(&body), None).await
+}
+
+/// Encodes an SSZ response body on the blocking pool, since large bodies would otherwise stall
+/// the async runtime.
+async fn encode_ssz_response<F>(
+    encode: F,
+    fork_name: Option<ForkName>,
+) -> Result<Response<Body>, StatusCode>
+where
+    F: FnOnce() -> Vec<u8> + Send + 'static,
+{
+    let body_content = tokio::task::spawn_blocking(encode).await.map_err(|e| {
+        error!(error = ?e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut response = Response::builder()
+        .status(200)
+        .header(CONTENT_TYPE, ContentType::Ssz.to_string());
+    if let Some(fork_name) = fork_name {
+        response = response.header(CONSENSUS_VERSION_HEADER, fork_name.to_string());
+    }
+    response.body(Body::from(body_content)).map_err(|e| {
+        error!(error = ?e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// Builds an extractor rejection carrying a JSON [`ErrorResponse`] body.
+fn rejection(status: StatusCode, message: impl Into<String>) -> Response {
+    let body = ErrorResponse {
+        code: status.as_u16(),
+        message: message.into(),
+        stacktraces: None,
+    };
+    (status, axum::Json(body)).into_response()
+}
+
+/// Fallback handler answering requests for unknown routes with a JSON `404`.
+pub async fn not_found() -> Response {
+    rejection(StatusCode::NOT_FOUND, "unknown route")
+}
+
+/// Response mapper, for use with `axum::middleware::map_response`, that turns the empty `405`
+/// axum sends for a known route requested with the wrong method into a JSON error. The `Allow`
+/// header is kept.
+pub async fn method_not_allowed(response: Response) -> Response {
+    if response.status() != StatusCode::METHOD_NOT_ALLOWED
+        || response.headers().contains_key(CONTENT_TYPE)
+    {
+        return response;
+    }
+
+    let mut json = rejection(
+        StatusCode::METHOD_NOT_ALLOWED,
+        "method not allowed for this route",
+    );
+    if let Some(allow) = response.headers().get(ALLOW) {
+        json.headers_mut().insert(ALLOW, allow.clone());
+    }
+    json
+}
+
+fn bytes_rejection(e: BytesRejection) -> Response {
+    rejection(e.status(), e.body_text())
+}
+
+fn unsupported_content_type(content_type: Option<&str>, expected: &str) -> Response {
+    rejection(
+        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        format!(
+            "expected {}, got {}",
+            expected,
+            content_type.unwrap_or("no content type")
+        ),
+    )
+}
+
+fn invalid_json(e: serde_json::Error) -> Response {
+    rejection(StatusCode::BAD_REQUEST, format!("invalid JSON body: {}", e))
+}
+
+fn invalid_ssz(e: ssz::DecodeError) -> Response {
+    rejection(
+        StatusCode::BAD_REQUEST,
+        format!("invalid SSZ body: {:?}", e),
+    )
+}
+
 #[must_use]
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Ssz<T>(pub T);
@@ -103,20 +297,20 @@ where
 
     async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
         let content_type_header = req.headers().get(CONTENT_TYPE);
-        let content_type = content_type_header.and_then(|value| value.to_str().ok());
-
-        if let Some(content_type) = content_type {
-            if content_type.starts_with("application/octet-stream") {
-                let bytes = Bytes::from_request(req, state)
-                    .await
-                    .map_err(IntoResponse::into_response)?;
-                return Ok(T::from_ssz_bytes(&bytes)
-                    .map(Ssz)
-                    .map_err(|_| StatusCode::BAD_REQUEST.into_response())?);
-            }
+        let raw_content_type = content_type_header.and_then(|value| value.to_str().ok());
+        let content_type = raw_content_type.and_then(|value| value.parse::<ContentType>().ok());
+
+        if let Some(ContentType::Ssz) = content_type {
+            let bytes = Bytes::from_request(req, state)
+                .await
+                .map_err(bytes_rejection)?;
+            return T::from_ssz_bytes(&bytes).map(Ssz).map_err(invalid_ssz);
         }
 
-        Err(StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response())
+        Err(unsupported_content_type(
+            raw_content_type,
+            "application/octet-stream",
+        ))
     }
 }
 
@@ -134,88 +328,204 @@ where
 
     async fn from_request(req: Request, _state: &S) -> Result<Self, Self::Rejection> {
         let headers = req.headers().clone();
-        let content_type = headers
+        let raw_content_type = headers
             .get(CONTENT_TYPE)
             .and_then(|value| value.to_str().ok());
+        let content_type = raw_content_type.and_then(|value| value.parse::<ContentType>().ok());
 
         let bytes = Bytes::from_request(req, _state)
             .await
-            .map_err(IntoResponse::into_response)?;
+            .map_err(bytes_rejection)?;
 
-        if let Some(content_type) = content_type {
-            if content_type.starts_with(&ContentType::Json.to_string()) {
-                let payload: T = serde_json::from_slice(&bytes)
-                    .map_err(|_| StatusCode::BAD_REQUEST.into_response())?;
-                return Ok(Self(payload));
+        match content_type {
+            Some(ContentType::Json) => {
+                let payload: T = serde_json::from_slice(&bytes).map_err(invalid_json)?;
+                Ok(Self(payload))
             }
-
-            if content_type.starts_with(&ContentType::Ssz.to_string()) {
-                let payload = T::from_ssz_bytes(&bytes)
-                    .map_err(|_| StatusCode::BAD_REQUEST.into_response())?;
-                return Ok(Self(payload));
+            Some(ContentType::Ssz) => {
+                let payload = T::from_ssz_bytes(&bytes).map_err(invalid_ssz)?;
+                Ok(Self(payload))
             }
+            None => Err(unsupported_content_type(
+                raw_content_type,
+                "application/json or application/octet-stream",
+            )),
         }
-
-        Err(StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response())
     }
 }
 
+/// Default cap on the decompressed size of a compressed request body.
+pub const DEFAULT_MAX_DECOMPRESSED_SIZE: usize = 10 * 1024 * 1024;
+
+/// Extracts a JSON or SSZ body that may be compressed with any [`ContentEncoding`].
+///
+/// Unknown encodings are rejected with `415 Unsupported Media Type`. Bodies that inflate
+/// beyond `MAX_DECOMPRESSED_SIZE` bytes are rejected with `413 Payload Too Large`.
 #[must_use]
 #[derive(Debug, Clone, Copy, Default)]
-pub struct JsonOrSszMaybeGzipped<T>(pub T);
+pub struct JsonOrSszMaybeGzipped<
+    T,
+    const MAX_DECOMPRESSED_SIZE: usize = DEFAULT_MAX_DECOMPRESSED_SIZE,
+>(pub T);
 
 #[async_trait]
-impl<T, S> FromRequest<S> for JsonOrSszMaybeGzipped<T>
+impl<T, S, const MAX_DECOMPRESSED_SIZE: usize> FromRequest<S>
+    for JsonOrSszMaybeGzipped<T, MAX_DECOMPRESSED_SIZE>
 where
     T: serde::de::DeserializeOwned + ssz::Decode + 'static,
     S: Send + Sync,
 {
     type Rejection = Response;
 
-    async fn from_request(req: Request, _state: &S) -> Result<Self, Self::Rejection> {
-        let headers = req.headers().clone();
-        let content_type = headers
-            .get(CONTENT_TYPE)
-            .and_then(|value| value.to_str().ok());
-        let content_encoding = headers
-            .get(CONTENT_ENCODING)
-            .and_then(|value| value.to_str().ok());
-
-        let bytes = Bytes::from_request(req, _state)
-            .await
-            .map_err(IntoResponse::into_response)?;
-
-        let decoded_bytes = if content_encoding == Some(&ContentEncoding::Gzip.to_string()) {
-            let mut decoder = GzDecoder::new(&bytes[..]);
-            let mut decoded = Vec::new();
-            decoder
-                .read_to_end(&mut decoded)
-                .map_err(|_| StatusCode::BAD_REQUEST.into_response())?;
-            decoded
-        } else {
-            bytes.to_vec()
-        };
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let (content_type, bytes) = decompressed_body(req, state, MAX_DECOMPRESSED_SIZE).await?;
 
-        if let Some(content_type) = content_type {
-            if content_type.starts_with(&ContentType::Json.to_string()) {
-                let payload: T = serde_json::from_slice(&decoded_bytes)
-                    .map_err(|_| StatusCode::BAD_REQUEST.into_response())?;
-                return Ok(Self(payload));
+        match content_type {
+            ContentType::Json => {
+                let payload: T = serde_json::from_slice(&bytes).map_err(invalid_json)?;
+                Ok(Self(payload))
+            }
+            ContentType::Ssz => {
+                let payload = T::from_ssz_bytes(&bytes).map_err(invalid_ssz)?;
+                Ok(Self(payload))
             }
+        }
+    }
+}
+
+/// Like [`JsonOrSszMaybeGzipped`], but decodes SSZ bodies by the fork named in the
+/// `Eth-Consensus-Version` header, rejecting SSZ bodies without one with `400 Bad Request`.
+#[must_use]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonOrSszWithFork<T, const MAX_DECOMPRESSED_SIZE: usize = DEFAULT_MAX_DECOMPRESSED_SIZE>(
+    pub T,
+);
+
+#[async_trait]
+impl<T, S, const MAX_DECOMPRESSED_SIZE: usize> FromRequest<S>
+    for JsonOrSszWithFork<T, MAX_DECOMPRESSED_SIZE>
+where
+    T: serde::de::DeserializeOwned + SszDecodeByFork + 'static,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let fork_name = req
+            .headers()
+            .get(CONSENSUS_VERSION_HEADER)
+            .map(|value| {
+                value
+                    .to_str()
+                    .ok()
+                    .and_then(|value| value.parse::<ForkName>().ok())
+                    .ok_or_else(|| {
+                        rejection(
+                            StatusCode::BAD_REQUEST,
+                            format!("invalid {} header", CONSENSUS_VERSION_HEADER),
+                        )
+                    })
+            })
+            .transpose()?;
+        let (content_type, bytes) = decompressed_body(req, state, MAX_DECOMPRESSED_SIZE).await?;
 
-            if content_type.starts_with(&ContentType::Ssz.to_string()) {
-                let payload = T::from_ssz_bytes(&decoded_bytes)
-                    .map_err(|_| StatusCode::BAD_REQUEST.into_response())?;
-                return Ok(Self(payload));
+        match content_type {
+            ContentType::Json => {
+                let payload: T = serde_json::from_slice(&bytes).map_err(invalid_json)?;
+                Ok(Self(payload))
             }
+            ContentType::Ssz => {
+                let fork_name = fork_name.ok_or_else(|| {
+                    rejection(
+                        StatusCode::BAD_REQUEST,
+                        format!("SSZ body requires a {} header", CONSENSUS_VERSION_HEADER),
+                    )
+                })?;
+                let payload = T::from_ssz_bytes_by_fork(&bytes, fork_name).map_err(invalid_ssz)?;
+                Ok(Self(payload))
+            }
+        }
+    }
+}
+
+/// Reads a JSON or SSZ request body, decompressing it according to its `Content-Encoding`.
+async fn decompressed_body<S: Send + Sync>(
+    req: Request,
+    state: &S,
+    max_decompressed_size: usize,
+) -> Result<(ContentType, Vec<u8>), Response> {
+    let headers = req.headers();
+    let raw_content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    let content_type = raw_content_type
+        .and_then(|value| value.parse::<ContentType>().ok())
+        .ok_or_else(|| {
+            unsupported_content_type(
+                raw_content_type,
+                "application/json or application/octet-stream",
+            )
+        })?;
+    let content_encoding = match headers.get(CONTENT_ENCODING) {
+        Some(value) => {
+            let value = value.to_str().unwrap_or_default();
+            value.parse::<ContentEncoding>().map_err(|_| {
+                rejection(
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    format!(
+                        "expected gzip, deflate or zstd content encoding, got {}",
+                        value
+                    ),
+                )
+            })?
         }
+        None => ContentEncoding::None,
+    };
+
+    let bytes = Bytes::from_request(req, state)
+        .await
+        .map_err(bytes_rejection)?;
 
-        Err(StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response())
+    let decoded_bytes = match content_encoding {
+        ContentEncoding::Gzip => decode_limited(GzDecoder::new(&bytes[..]), max_decompressed_size)?,
+        ContentEncoding::Deflate => {
+            decode_limited(ZlibDecoder::new(&bytes[..]), max_decompressed_size)?
+        }
+        ContentEncoding::Zstd => {
+            let decoder = zstd::stream::read::Decoder::new(&bytes[..]).map_err(|e| {
+                rejection(StatusCode::BAD_REQUEST, format!("invalid zstd body: {}", e))
+            })?;
+            decode_limited(decoder, max_decompressed_size)?
+        }
+        ContentEncoding::None => bytes.to_vec(),
+    };
+    Ok((content_type, decoded_bytes))
+}
+
+/// Reads a decompressing `decoder` to the end, rejecting output larger than `max_size` bytes.
+fn decode_limited<R: Read>(decoder: R, max_size: usize) -> Result<Vec<u8>, Response> {
+    // Read one byte past the limit so oversized bodies can be told apart.
+    let mut decoded = Vec::new();
+    decoder
+        .take(max_size as u64 + 1)
+        .read_to_end(&mut decoded)
+        .map_err(|e| {
+            rejection(
+                StatusCode::BAD_REQUEST,
+                format!("failed to decompress body: {}", e),
+            )
+        })?;
+    if decoded.len() > max_size {
+        return Err(rejection(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("decompressed body exceeds {} bytes", max_size),
+        ));
     }
+    Ok(decoded)
 }
 
 // Headers
-#[derive(Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub enum ContentType {
     #[default]
     Json,
@@ -231,19 +541,87 @@ impl std::fmt::Display for ContentType {
     }
 }
 
+/// Parses a `Content-Type` value, ignoring any parameters such as `charset`.
+///
+/// SSZ is recognised as `application/octet-stream`, `application/ssz` or any
+/// `application/*+ssz` suffix type.
+impl std::str::FromStr for ContentType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let media_type = value
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        match media_type.as_str() {
+            "application/json" => Ok(ContentType::Json),
+            "application/octet-stream" | "application/ssz" => Ok(ContentType::Ssz),
+            media_type
+                if media_type.starts_with("application/") && media_type.ends_with("+ssz") =>
+            {
+                Ok(ContentType::Ssz)
+            }
+            _ => Err(format!("unknown content type: {}", value)),
+        }
+    }
+}
+
 impl From<String> for ContentType {
     fn from(value: String) -> Self {
-        match value.as_str() {
-            "application/json" => ContentType::Json,
-            "application/octet-stream" => ContentType::Ssz,
-            _ => panic!("unknown content type: {}", value),
+        value.parse().unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+/// Picks the response content type from an `Accept` header value.
+///
+/// A missing header means the client accepts anything, in which case the first of `available`
+/// is used. Otherwise the supported media range with the highest q-value wins, with ties going
+/// to the earlier entry. Returns `None` when nothing in `available` is acceptable, which should
+/// be answered with `406 Not Acceptable`.
+pub fn negotiate_content_type(
+    accept: Option<&str>,
+    available: &[ContentType],
+) -> Option<ContentType> {
+    let Some(accept) = accept else {
+        return available.first().copied();
+    };
+
+    let mut best: Option<(ContentType, f32)> = None;
+    for entry in accept.split(',') {
+        let mut parts = entry.split(';');
+        let media_range = parts.next().unwrap_or_default().trim();
+        let q = parts
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .find_map(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if q <= 0.0 {
+            continue;
+        }
+
+        let candidate = match media_range {
+            "*/*" | "application/*" => available.first().copied(),
+            media_range => media_range
+                .parse::<ContentType>()
+                .ok()
+                .filter(|content_type| available.contains(content_type)),
+        };
+        if let Some(content_type) = candidate {
+            if !matches!(best, Some((_, best_q)) if best_q >= q) {
+                best = Some((content_type, q));
+            }
         }
     }
+
+    best.map(|(content_type, _)| content_type)
 }
 
 #[derive(Default, Clone, Copy)]
 pub enum ContentEncoding {
     Gzip,
+    Deflate,
+    Zstd,
     #[default]
     None,
 }
@@ -252,18 +630,31 @@ impl std::fmt::Display for ContentEncoding {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ContentEncoding::Gzip => write!(f, "gzip"),
+            ContentEncoding::Deflate => write!(f, "deflate"),
+            ContentEncoding::Zstd => write!(f, "zstd"),
             ContentEncoding::None => write!(f, ""),
         }
     }
 }
 
+impl std::str::FromStr for ContentEncoding {
+    type Err = String;
+
+    /// Content codings are case-insensitive, so `GZIP` is read as `gzip`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "gzip" => Ok(ContentEncoding::Gzip),
+            "deflate" => Ok(ContentEncoding::Deflate),
+            "zstd" => Ok(ContentEncoding::Zstd),
+            "" | "identity" => Ok(ContentEncoding::None),
+            _ => Err(format!("unknown content encoding: {}", value)),
+        }
+    }
+}
+
 impl From<String> for ContentEncoding {
     fn from(value: String) -> Self {
-        match value.as_ref() {
-            "gzip" => ContentEncoding::Gzip,
-            "" => ContentEncoding::None,
-            _ => panic!("unknown content encoding: {}", value),
-        }
+        value.parse().unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
@@ -302,18 +693,204 @@ where
             .get(CONSENSUS_VERSION_HEADER)
             .and_then(|value| value.to_str().ok())
             .and_then(|s| s.parse().ok())
-            .ok_or(StatusCode::BAD_REQUEST.into_response())?;
+            .ok_or_else(|| {
+                rejection(
+                    StatusCode::BAD_REQUEST,
+                    format!("missing or invalid {} header", CONSENSUS_VERSION_HEADER),
+                )
+            })?;
 
         let bytes = Bytes::from_request(req, _state)
             .await
-            .map_err(IntoResponse::into_response)?;
+            .map_err(bytes_rejection)?;
 
         let result = ForkVersionDeserialize::deserialize_by_fork::<serde_json::Value>(
-            serde_json::de::from_slice(&bytes)
-                .map_err(|_| StatusCode::BAD_REQUEST.into_response())?,
+            serde_json::de::from_slice(&bytes).map_err(invalid_json)?,
             fork_name,
         )
-        .map_err(|_| StatusCode::BAD_REQUEST.into_response())?;
+        .map_err(|e| {
+            rejection(
+                StatusCode::BAD_REQUEST,
+                format!("invalid {} body: {}", fork_name, e),
+            )
+        })?;
         Ok(Self(result))
     }
 }
+
+// Chain context
+/// The chain a client talks to, used to compute signing domains.
+#[derive(Debug, Clone)]
+pub struct NetworkConfig {
+    pub spec: ChainSpec,
+    pub genesis_validators_root: Hash256,
+}
+
+impl NetworkConfig {
+    pub fn new(spec: ChainSpec, genesis_validators_root: Hash256) -> Self {
+        Self {
+            spec,
+            genesis_validators_root,
+        }
+    }
+
+    /// Domain for validator registrations and builder bids, which is independent of the fork
+    /// and genesis validators root.
+    pub fn builder_domain(&self) -> Hash256 {
+        compute_builder_domain(&self.spec)
+    }
+
+    /// Domain for `domain` at `epoch`, using the fork scheduled for that epoch.
+    pub fn get_domain(&self, epoch: Epoch, domain: Domain) -> Hash256 {
+        let fork = self.spec.fork_at_epoch(epoch);
+        self.spec
+            .get_domain(epoch, domain, &fork, self.genesis_validators_root)
+    }
+}
+
+// Client configuration
+/// HTTP connection settings shared by the API clients.
+///
+/// The defaults match `reqwest`'s. A proposer querying many relays benefits from keeping one
+/// warm connection per relay between slots: a `pool_max_idle_per_host` of 1-2 with a
+/// `pool_idle_timeout` longer than a slot (e.g. 30s) avoids a fresh TLS handshake on the
+/// latency-sensitive `get_header` path. Only enable `http2_prior_knowledge` for endpoints known
+/// to speak HTTP/2.
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    /// Overrides the client's default `User-Agent`, e.g. to identify the proposer software to
+    /// relay operators.
+    pub user_agent: Option<String>,
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout: Option<Duration>,
+    pub http2_prior_knowledge: bool,
+    /// Responses with a larger body are rejected with `ResponseTooLarge` instead of being
+    /// buffered in full. Unlimited by default.
+    pub max_response_size: Option<usize>,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: None,
+            pool_max_idle_per_host: usize::MAX,
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            http2_prior_knowledge: false,
+            max_response_size: None,
+        }
+    }
+}
+
+// Server middleware
+/// Middleware applied to an API router by [`with_middleware`]. Everything is disabled by
+/// default.
+#[derive(Debug, Clone, Default)]
+pub struct MiddlewareConfig {
+    /// CORS policy, e.g. [`CorsLayer::permissive`] for browser-based dashboards.
+    pub cors: Option<CorsLayer>,
+    /// Assign an `x-request-id` to requests without one and echo it in the response.
+    pub request_id: bool,
+    /// Abort requests that take longer than this with `408 Request Timeout`.
+    pub timeout: Option<Duration>,
+    /// Reject request bodies larger than this many bytes with `413 Payload Too Large`.
+    pub body_limit: Option<usize>,
+}
+
+/// Wraps `router` in the middleware enabled by `config`.
+pub fn with_middleware(router: Router, config: &MiddlewareConfig) -> Router {
+    let mut router = router;
+    if let Some(limit) = config.body_limit {
+        router = router.layer(RequestBodyLimitLayer::new(limit));
+    }
+    if let Some(timeout) = config.timeout {
+        router = router.layer(TimeoutLayer::new(timeout));
+    }
+    if config.request_id {
+        // The id must be set before the propagation layer sees the request, so it's added last
+        // to be outermost.
+        router = router
+            .layer(PropagateRequestIdLayer::x_request_id())
+            .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid));
+    }
+    if let Some(cors) = &config.cors {
+        router = router.layer(cors.clone());
+    }
+    router
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::to_bytes, routing::post};
+    use tower::ServiceExt;
+
+    /// Posts `body` to a route extracting a `JsonOrSsz<u64>`, returning the status and the
+    /// rejection body.
+    async fn post_json_or_ssz(
+        content_type: &str,
+        body: &'static [u8],
+    ) -> (StatusCode, ErrorResponse) {
+        let router = Router::new().route(
+            "/",
+            post(|JsonOrSsz(value): JsonOrSsz<u64>| async move { value.to_string() }),
+        );
+        let response = router
+            .oneshot(
+                http::Request::post("/")
+                    .header(CONTENT_TYPE, content_type)
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let status = response.status();
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[test]
+    fn content_encoding_is_case_insensitive() {
+        assert!(matches!(
+            "GZIP".parse::<ContentEncoding>(),
+            Ok(ContentEncoding::Gzip)
+        ));
+        assert!(matches!(
+            " Deflate ".parse::<ContentEncoding>(),
+            Ok(ContentEncoding::Deflate)
+        ));
+        assert!(matches!(
+            "Identity".parse::<ContentEncoding>(),
+            Ok(ContentEncoding::None)
+        ));
+        assert!("br".parse::<ContentEncoding>().is_err());
+    }
+
+    #[tokio::test]
+    async fn malformed_json_is_rejected_with_json_error() {
+        let (status, error) = post_json_or_ssz("application/json", b"{").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.code, 400);
+        assert!(error.message.starts_with("invalid JSON body: "));
+    }
+
+    #[tokio::test]
+    async fn bad_ssz_is_rejected_with_json_error() {
+        let (status, error) = post_json_or_ssz("application/octet-stream", &[1, 2, 3]).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.code, 400);
+        assert!(error.message.starts_with("invalid SSZ body: "));
+    }
+
+    #[tokio::test]
+    async fn wrong_content_type_is_rejected_with_json_error() {
+        let (status, error) = post_json_or_ssz("text/plain", b"1").await;
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(error.code, 415);
+        assert_eq!(
+            error.message,
+            "expected application/json or application/octet-stream, got text/plain"
+        );
+    }
+}
@@ -2,6 +2,7 @@ pub use beacon_api_types::*;
 use serde::{Deserialize, Serialize};
 use serde_utils::quoted_u64::Quoted;
 use ssz_derive::{Decode, Encode};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 // Builder API requests
 
@@ -28,8 +29,10 @@ pub struct SubmitBlockRequest<E: EthSpec> {
     #[superstruct(flatten)]
     pub execution_payload: ExecutionPayload<E>,
     pub signature: Signature,
-    #[superstruct(only(Deneb))]
+    #[superstruct(only(Deneb, Electra))]
     pub blobs_bundle: BlobsBundle<E>,
+    #[superstruct(only(Electra))]
+    pub execution_requests: ExecutionRequests<E>,
 }
 
 impl<E: EthSpec> ssz::Decode for SubmitBlockRequest<E> {
@@ -54,17 +57,115 @@ impl<E: EthSpec> ssz::Decode for SubmitBlockRequest<E> {
     }
 }
 
+impl<E: EthSpec> SubmitBlockRequest<E> {
+    /// Checks that the bid trace describes the submitted execution payload.
+    pub fn validate_against_payload(&self) -> Result<(), BidTraceMismatch> {
+        let (block_hash, parent_hash, gas_limit, gas_used) = match self {
+            Self::Bellatrix(request) => (
+                request.execution_payload.block_hash,
+                request.execution_payload.parent_hash,
+                request.execution_payload.gas_limit,
+                request.execution_payload.gas_used,
+            ),
+            Self::Capella(request) => (
+                request.execution_payload.block_hash,
+                request.execution_payload.parent_hash,
+                request.execution_payload.gas_limit,
+                request.execution_payload.gas_used,
+            ),
+            Self::Deneb(request) => (
+                request.execution_payload.block_hash,
+                request.execution_payload.parent_hash,
+                request.execution_payload.gas_limit,
+                request.execution_payload.gas_used,
+            ),
+            Self::Electra(request) => (
+                request.execution_payload.block_hash,
+                request.execution_payload.parent_hash,
+                request.execution_payload.gas_limit,
+                request.execution_payload.gas_used,
+            ),
+        };
+
+        let bid_trace = self.message();
+        BidTraceMismatch::check("block_hash", bid_trace.block_hash, block_hash)?;
+        BidTraceMismatch::check("parent_hash", bid_trace.parent_hash, parent_hash)?;
+        BidTraceMismatch::check("gas_limit", bid_trace.gas_limit, gas_limit)?;
+        BidTraceMismatch::check("gas_used", bid_trace.gas_used, gas_used)
+    }
+}
+
+/// A bid trace field that disagrees with the execution payload it was submitted with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BidTraceMismatch {
+    pub field: &'static str,
+    pub bid_trace: String,
+    pub payload: String,
+}
+
+impl BidTraceMismatch {
+    fn check<T: PartialEq + std::fmt::Display>(
+        field: &'static str,
+        bid_trace: T,
+        payload: T,
+    ) -> Result<(), Self> {
+        if bid_trace == payload {
+            return Ok(());
+        }
+        Err(Self {
+            field,
+            bid_trace: bid_trace.to_string(),
+            payload: payload.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for BidTraceMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "bid trace {} {} does not match execution payload {}",
+            self.field, self.bid_trace, self.payload
+        )
+    }
+}
+
+impl std::error::Error for BidTraceMismatch {}
+
 // Data API requests
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum OrderBy {
     #[serde(rename = "value")]
     Value,
+    /// Highest value first.
+    #[default]
     #[serde(rename = "-value")]
     NegativeValue,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+impl std::fmt::Display for OrderBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderBy::Value => write!(f, "value"),
+            OrderBy::NegativeValue => write!(f, "-value"),
+        }
+    }
+}
+
+impl std::str::FromStr for OrderBy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "value" => Ok(OrderBy::Value),
+            "-value" => Ok(OrderBy::NegativeValue),
+            _ => Err(format!("unknown order: {}", value)),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GetDeliveredPayloadsQueryParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub slot: Option<Slot>,
@@ -84,6 +185,100 @@ pub struct GetDeliveredPayloadsQueryParams {
     pub order_by: Option<OrderBy>,
 }
 
+impl GetDeliveredPayloadsQueryParams {
+    pub fn builder() -> GetDeliveredPayloadsQueryParamsBuilder {
+        GetDeliveredPayloadsQueryParamsBuilder::default()
+    }
+
+    /// Checks for filter combinations the relay spec disallows.
+    pub fn validate(&self) -> Result<(), QueryParamsError> {
+        if self.slot.is_some() && self.cursor.is_some() {
+            return Err(QueryParamsError::SlotAndCursor);
+        }
+        if self.block_hash.is_some() && self.block_number.is_some() {
+            return Err(QueryParamsError::BlockHashAndBlockNumber);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryParamsError {
+    /// `slot` and `cursor` are mutually exclusive.
+    SlotAndCursor,
+    /// `block_hash` and `block_number` are mutually exclusive.
+    BlockHashAndBlockNumber,
+}
+
+impl std::fmt::Display for QueryParamsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryParamsError::SlotAndCursor => {
+                write!(f, "cannot specify both slot and cursor")
+            }
+            QueryParamsError::BlockHashAndBlockNumber => {
+                write!(f, "cannot specify both block_hash and block_number")
+            }
+        }
+    }
+}
+
+impl std::error::Error for QueryParamsError {}
+
+#[derive(Default, Debug, Clone)]
+pub struct GetDeliveredPayloadsQueryParamsBuilder {
+    params: GetDeliveredPayloadsQueryParams,
+}
+
+impl GetDeliveredPayloadsQueryParamsBuilder {
+    pub fn slot(mut self, slot: Slot) -> Self {
+        self.params.slot = Some(slot);
+        self
+    }
+
+    pub fn cursor(mut self, cursor: Slot) -> Self {
+        self.params.cursor = Some(cursor);
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.params.limit = Some(Slot::new(limit));
+        self
+    }
+
+    pub fn block_hash(mut self, block_hash: ExecutionBlockHash) -> Self {
+        self.params.block_hash = Some(block_hash);
+        self
+    }
+
+    pub fn block_number(mut self, block_number: u64) -> Self {
+        self.params.block_number = Some(Quoted {
+            value: block_number,
+        });
+        self
+    }
+
+    pub fn proposer_pubkey(mut self, proposer_pubkey: PublicKeyBytes) -> Self {
+        self.params.proposer_pubkey = Some(proposer_pubkey);
+        self
+    }
+
+    pub fn builder_pubkey(mut self, builder_pubkey: PublicKeyBytes) -> Self {
+        self.params.builder_pubkey = Some(builder_pubkey);
+        self
+    }
+
+    pub fn order_by(mut self, order_by: OrderBy) -> Self {
+        self.params.order_by = Some(order_by);
+        self
+    }
+
+    pub fn build(self) -> Result<GetDeliveredPayloadsQueryParams, QueryParamsError> {
+        self.params.validate()?;
+        Ok(self.params)
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GetReceivedBidsQueryParams {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -96,6 +291,8 @@ pub struct GetReceivedBidsQueryParams {
     pub builder_pubkey: Option<PublicKeyBytes>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limit: Option<Slot>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_by: Option<OrderBy>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -103,6 +300,12 @@ pub struct GetValidatorRegistrationQueryParams {
     pub pubkey: PublicKeyBytes,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GetBlobsBundleQueryParams {
+    pub slot: Slot,
+    pub block_hash: ExecutionBlockHash,
+}
+
 #[superstruct(
     variants(Bellatrix, Capella, Deneb, Electra),
     variant_attributes(
@@ -119,8 +322,10 @@ pub struct HeaderSubmission<E: EthSpec> {
     pub bid_trace: BidTraceV1,
     #[superstruct(flatten)]
     pub execution_payload_header: ExecutionPayloadHeader<E>,
-    #[superstruct(only(Deneb))]
+    #[superstruct(only(Deneb, Electra))]
     pub blobs_bundle: BlobsBundle<E>,
+    #[superstruct(only(Electra))]
+    pub execution_requests: ExecutionRequests<E>,
 }
 
 #[superstruct(
@@ -141,6 +346,90 @@ pub struct SignedHeaderSubmission<E: EthSpec> {
     pub signature: Signature,
 }
 
+/// Decodes the variant for `fork_name`. The derived transparent decoding picks the first variant
+/// whose layout fits the bytes, which can misdecode forks with look-alike headers.
+impl<E: EthSpec> SszDecodeByFork for SignedHeaderSubmission<E> {
+    fn from_ssz_bytes_by_fork(bytes: &[u8], fork_name: ForkName) -> Result<Self, ssz::DecodeError> {
+        match fork_name {
+            ForkName::Bellatrix => {
+                SignedHeaderSubmissionBellatrix::from_ssz_bytes(bytes).map(Self::Bellatrix)
+            }
+            ForkName::Capella => {
+                SignedHeaderSubmissionCapella::from_ssz_bytes(bytes).map(Self::Capella)
+            }
+            ForkName::Deneb => SignedHeaderSubmissionDeneb::from_ssz_bytes(bytes).map(Self::Deneb),
+            ForkName::Electra => {
+                SignedHeaderSubmissionElectra::from_ssz_bytes(bytes).map(Self::Electra)
+            }
+            _ => Err(ssz::DecodeError::BytesInvalid(format!(
+                "no header submission for fork {}",
+                fork_name
+            ))),
+        }
+    }
+}
+
+impl<E: EthSpec> SignedHeaderSubmission<E> {
+    pub fn bid_trace(&self) -> &BidTraceV1 {
+        match self {
+            Self::Bellatrix(submission) => &submission.message.bid_trace,
+            Self::Capella(submission) => &submission.message.bid_trace,
+            Self::Deneb(submission) => &submission.message.bid_trace,
+            Self::Electra(submission) => &submission.message.bid_trace,
+        }
+    }
+
+    /// Builds the bid served to proposers from `get_header`, valued at the bid trace value and
+    /// signed by the relay with `signer` over the builder domain.
+    ///
+    /// The bid's pubkey is the relay's rather than the builder's: proposers check that it
+    /// matches the relay they queried and verify the signature against it, and only the relay
+    /// can sign. The builder's key is kept in the bid trace.
+    pub fn to_signed_builder_bid(
+        &self,
+        signer: &SecretKey,
+        spec: &ChainSpec,
+    ) -> builder_bid::SignedBuilderBid<E> {
+        let value = self.bid_trace().value;
+        let pubkey = signer.public_key().compress();
+        let message = match self {
+            Self::Bellatrix(submission) => {
+                builder_bid::BuilderBid::Bellatrix(builder_bid::BuilderBidBellatrix {
+                    header: submission.message.execution_payload_header.clone(),
+                    value,
+                    pubkey,
+                })
+            }
+            Self::Capella(submission) => {
+                builder_bid::BuilderBid::Capella(builder_bid::BuilderBidCapella {
+                    header: submission.message.execution_payload_header.clone(),
+                    value,
+                    pubkey,
+                })
+            }
+            Self::Deneb(submission) => {
+                builder_bid::BuilderBid::Deneb(builder_bid::BuilderBidDeneb {
+                    header: submission.message.execution_payload_header.clone(),
+                    blob_kzg_commitments: submission.message.blobs_bundle.commitments.clone(),
+                    value,
+                    pubkey,
+                })
+            }
+            Self::Electra(submission) => {
+                builder_bid::BuilderBid::Electra(builder_bid::BuilderBidElectra {
+                    header: submission.message.execution_payload_header.clone(),
+                    blob_kzg_commitments: submission.message.blobs_bundle.commitments.clone(),
+                    execution_requests: submission.message.execution_requests.clone(),
+                    value,
+                    pubkey,
+                })
+            }
+        };
+        let signature = signer.sign(message.signing_root(compute_builder_domain(spec)));
+        builder_bid::SignedBuilderBid { message, signature }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode)]
 pub struct Cancellation {
     #[serde(with = "serde_utils::quoted_u64")]
@@ -157,7 +446,39 @@ pub struct SignedCancellation {
 }
 
 // Websockets types
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TopBidsFormat {
+    /// Updates are sent as JSON text frames.
+    #[default]
+    Json,
+    /// Updates are sent as SSZ-encoded binary frames.
+    Ssz,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopBidsQueryParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<TopBidsFormat>,
+    /// Only stream updates for this slot.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<Slot>,
+    /// Only stream updates from this builder.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub builder_pubkey: Option<PublicKeyBytes>,
+}
+
+impl TopBidsQueryParams {
+    /// Whether `update` passes the `slot` and `builder_pubkey` filters.
+    pub fn matches(&self, update: &TopBidUpdate) -> bool {
+        self.slot.is_none_or(|slot| update.slot == slot)
+            && self
+                .builder_pubkey
+                .is_none_or(|builder_pubkey| update.builder_pubkey == builder_pubkey)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Encode, Decode)]
 pub struct TopBidUpdate {
     #[serde(with = "serde_utils::quoted_u64")]
     pub timestamp: u64,
@@ -173,17 +494,28 @@ pub struct TopBidUpdate {
 }
 
 // Builder API responses
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum Filtering {
+    #[default]
     Regional,
     Global,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ValidatorPreferences {
+    #[serde(default)]
     pub filtering: Filtering,
     pub trusted_builders: Option<Vec<String>>,
+    /// Whether the proposer accepts the relay delaying `get_header` responses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header_delay: Option<bool>,
+    /// Whether the relay should gossip blobs on the proposer's behalf.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gossip_blobs: Option<bool>,
+    /// Identifier of the builder the proposer is affiliated with, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub builder_id: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -192,6 +524,7 @@ pub struct ValidatorsResponse {
     #[serde(with = "serde_utils::quoted_u64")]
     pub validator_index: u64,
     pub entry: SignedValidatorRegistrationData,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub preferences: Option<ValidatorPreferences>,
 }
 
@@ -213,7 +546,9 @@ pub struct BidTraceV1 {
     pub value: Uint256,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// SSZ encodes as the fields of the [`BidTraceV1`] followed by `block_number` and `num_tx`,
+/// since a fixed-size nested container is inlined.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Encode, Decode)]
 pub struct BidTraceV2 {
     #[serde(flatten)]
     pub bid_trace: BidTraceV1,
@@ -227,12 +562,77 @@ pub struct BidTraceV2 {
 pub struct BidTraceV2WithTimestamp {
     #[serde(flatten)]
     pub bid_trace: BidTraceV2,
-    #[serde(with = "serde_utils::quoted_i64")]
+    /// Some relays only populate one of `timestamp` and `timestamp_ms`; the other defaults to
+    /// zero.
+    #[serde(default, with = "serde_utils::quoted_i64")]
     pub timestamp: i64,
-    #[serde(with = "serde_utils::quoted_i64")]
+    #[serde(default, with = "serde_utils::quoted_i64")]
     pub timestamp_ms: i64,
 }
 
+impl BidTraceV2WithTimestamp {
+    /// When the relay received the bid, using `timestamp_ms` if set and `timestamp` otherwise.
+    /// Unset or negative timestamps are reported as the Unix epoch.
+    pub fn received_at(&self) -> SystemTime {
+        let since_epoch = if self.timestamp_ms > 0 {
+            Duration::from_millis(self.timestamp_ms as u64)
+        } else {
+            Duration::from_secs(self.timestamp.max(0) as u64)
+        };
+        UNIX_EPOCH + since_epoch
+    }
+}
+
+/// `timestamp` and `timestamp_ms` are SSZ encoded as their two's complement `u64`s, following
+/// the fields of the [`BidTraceV2`].
+impl ssz::Encode for BidTraceV2WithTimestamp {
+    fn is_ssz_fixed_len() -> bool {
+        true
+    }
+
+    fn ssz_fixed_len() -> usize {
+        <BidTraceV2 as ssz::Encode>::ssz_fixed_len() + 2 * <u64 as ssz::Encode>::ssz_fixed_len()
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        <Self as ssz::Encode>::ssz_fixed_len()
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        ssz::Encode::ssz_append(&self.bid_trace, buf);
+        ssz::Encode::ssz_append(&(self.timestamp as u64), buf);
+        ssz::Encode::ssz_append(&(self.timestamp_ms as u64), buf);
+    }
+}
+
+impl ssz::Decode for BidTraceV2WithTimestamp {
+    fn is_ssz_fixed_len() -> bool {
+        true
+    }
+
+    fn ssz_fixed_len() -> usize {
+        <Self as ssz::Encode>::ssz_fixed_len()
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, ssz::DecodeError> {
+        let expected = <Self as ssz::Decode>::ssz_fixed_len();
+        if bytes.len() != expected {
+            return Err(ssz::DecodeError::InvalidByteLength {
+                len: bytes.len(),
+                expected,
+            });
+        }
+
+        let (bid_trace, timestamps) = bytes.split_at(<BidTraceV2 as ssz::Decode>::ssz_fixed_len());
+        let (timestamp, timestamp_ms) = timestamps.split_at(<u64 as ssz::Decode>::ssz_fixed_len());
+        Ok(Self {
+            bid_trace: <BidTraceV2 as ssz::Decode>::from_ssz_bytes(bid_trace)?,
+            timestamp: <u64 as ssz::Decode>::from_ssz_bytes(timestamp)? as i64,
+            timestamp_ms: <u64 as ssz::Decode>::from_ssz_bytes(timestamp_ms)? as i64,
+        })
+    }
+}
+
 #[superstruct(
     variants(Bellatrix, Capella, Deneb, Electra),
     variant_attributes(
@@ -272,10 +672,378 @@ pub struct HeaderResponse<E: EthSpec> {
     pub pubkey: PublicKeyBytes,
 }
 
+impl<E: EthSpec> HeaderResponse<E> {
+    /// Consumes the response, returning its execution payload header whatever the fork.
+    pub fn to_execution_payload_header(self) -> ExecutionPayloadHeader<E> {
+        map_header_response_into_execution_payload_header!(self, |inner, cons| cons(
+            inner.execution_payload_header
+        ))
+    }
+}
+
+/// Body returned by the relay on a successful block or header submission.
+///
+/// The relay spec doesn't define one, so this is `None` when the relay responds with an empty
+/// body and otherwise holds whatever acknowledgement the relay sent back.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SubmitBlockResponse(pub Option<serde_json::Value>);
+
+impl SubmitBlockResponse {
+    /// Acknowledgement for a submission the relay had already received.
+    pub fn duplicate() -> Self {
+        Self(Some(serde_json::json!({ "duplicate": true })))
+    }
+
+    pub fn is_duplicate(&self) -> bool {
+        self.0
+            .as_ref()
+            .and_then(|body| body.get("duplicate"))
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false)
+    }
+}
+
+/// Forks and optional endpoints a relay supports, so builders and proposers can check
+/// compatibility before submitting.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RelayCapabilities {
+    /// Forks the relay accepts submissions for.
+    pub forks: Vec<ForkName>,
+    /// Whether the optimistic v2 header and payload submission endpoints are served.
+    #[serde(default)]
+    pub optimistic_v2: bool,
+    /// Whether submissions may be sent as SSZ.
+    #[serde(default)]
+    pub ssz_submissions: bool,
+}
+
+impl RelayCapabilities {
+    pub fn supports_fork(&self, fork_name: ForkName) -> bool {
+        self.forks.contains(&fork_name)
+    }
+}
+
 // Builder API response types
 pub type GetValidatorsResponse = Vec<ValidatorsResponse>;
+pub type GetCapabilitiesResponse = RelayCapabilities;
 
 // Data API response types
 pub type GetDeliveredPayloadsResponse = Vec<BidTraceV2>;
 pub type GetReceivedBidsResponse = Vec<BidTraceV2WithTimestamp>;
 pub type GetValidatorRegistrationResponse = SignedValidatorRegistrationData;
+pub type GetBlobsBundleResponse<E> = BlobsBundle<E>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use ssz::{Decode, Encode};
+
+    type E = MainnetEthSpec;
+
+    fn bid_trace() -> BidTraceV1 {
+        BidTraceV1 {
+            slot: Slot::new(1),
+            parent_hash: ExecutionBlockHash::from_root(Hash256::repeat_byte(1)),
+            block_hash: ExecutionBlockHash::from_root(Hash256::repeat_byte(2)),
+            builder_pubkey: PublicKeyBytes::empty(),
+            proposer_pubkey: PublicKeyBytes::empty(),
+            proposer_fee_recipient: Address::repeat_byte(3),
+            gas_limit: 30_000_000,
+            gas_used: 15_000_000,
+            value: Uint256::from(1_000_000_000u64),
+        }
+    }
+
+    fn header_submission(fork_name: ForkName) -> SignedHeaderSubmission<E> {
+        let bid_trace = bid_trace();
+        let signature = Signature::empty();
+        match fork_name {
+            ForkName::Bellatrix => {
+                SignedHeaderSubmission::Bellatrix(SignedHeaderSubmissionBellatrix {
+                    message: HeaderSubmissionBellatrix {
+                        bid_trace,
+                        execution_payload_header: Default::default(),
+                    },
+                    signature,
+                })
+            }
+            ForkName::Capella => SignedHeaderSubmission::Capella(SignedHeaderSubmissionCapella {
+                message: HeaderSubmissionCapella {
+                    bid_trace,
+                    execution_payload_header: Default::default(),
+                },
+                signature,
+            }),
+            ForkName::Deneb => SignedHeaderSubmission::Deneb(SignedHeaderSubmissionDeneb {
+                message: HeaderSubmissionDeneb {
+                    bid_trace,
+                    execution_payload_header: Default::default(),
+                    blobs_bundle: Default::default(),
+                },
+                signature,
+            }),
+            ForkName::Electra => SignedHeaderSubmission::Electra(SignedHeaderSubmissionElectra {
+                message: HeaderSubmissionElectra {
+                    bid_trace,
+                    execution_payload_header: Default::default(),
+                    blobs_bundle: Default::default(),
+                    execution_requests: Default::default(),
+                },
+                signature,
+            }),
+            fork_name => panic!("no header submission for fork {}", fork_name),
+        }
+    }
+
+    fn submission_fork(submission: &SignedHeaderSubmission<E>) -> ForkName {
+        match submission {
+            SignedHeaderSubmission::Bellatrix(_) => ForkName::Bellatrix,
+            SignedHeaderSubmission::Capella(_) => ForkName::Capella,
+            SignedHeaderSubmission::Deneb(_) => ForkName::Deneb,
+            SignedHeaderSubmission::Electra(_) => ForkName::Electra,
+        }
+    }
+
+    #[test]
+    fn header_submission_ssz_round_trips_by_fork() {
+        for fork_name in [
+            ForkName::Bellatrix,
+            ForkName::Capella,
+            ForkName::Deneb,
+            ForkName::Electra,
+        ] {
+            let submission = header_submission(fork_name);
+            let bytes = submission.as_ssz_bytes();
+
+            let decoded = SignedHeaderSubmission::<E>::from_ssz_bytes_by_fork(&bytes, fork_name)
+                .unwrap_or_else(|e| panic!("failed to decode {} submission: {:?}", fork_name, e));
+            assert_eq!(submission_fork(&decoded), fork_name);
+            assert_eq!(decoded.bid_trace(), submission.bid_trace());
+            assert_eq!(decoded.as_ssz_bytes(), bytes);
+        }
+    }
+
+    #[test]
+    fn header_submission_ssz_rejects_forks_without_submissions() {
+        let bytes = header_submission(ForkName::Deneb).as_ssz_bytes();
+        assert!(
+            SignedHeaderSubmission::<E>::from_ssz_bytes_by_fork(&bytes, ForkName::Base).is_err()
+        );
+    }
+
+    fn blobs_bundle() -> BlobsBundle<E> {
+        BlobsBundle {
+            commitments: vec![KzgCommitment::empty_for_testing()].into(),
+            proofs: vec![KzgProof::empty()].into(),
+            blobs: vec![Blob::<E>::default()].into(),
+        }
+    }
+
+    #[test]
+    fn deneb_header_submission_converts_to_signed_builder_bid() {
+        let spec = ChainSpec::mainnet();
+        let signer = SecretKey::random();
+        let header = ExecutionPayloadHeaderDeneb::<E> {
+            block_hash: bid_trace().block_hash,
+            parent_hash: bid_trace().parent_hash,
+            ..Default::default()
+        };
+        let blobs_bundle = blobs_bundle();
+        let submission = SignedHeaderSubmission::Deneb(SignedHeaderSubmissionDeneb {
+            message: HeaderSubmissionDeneb {
+                bid_trace: bid_trace(),
+                execution_payload_header: header.clone(),
+                blobs_bundle: blobs_bundle.clone(),
+            },
+            signature: Signature::empty(),
+        });
+
+        let bid = submission.to_signed_builder_bid(&signer, &spec);
+        let builder_bid::BuilderBid::Deneb(message) = &bid.message else {
+            panic!("expected a Deneb bid");
+        };
+        assert_eq!(message.header, header);
+        assert_eq!(message.blob_kzg_commitments, blobs_bundle.commitments);
+        assert_eq!(message.value, bid_trace().value);
+        assert_eq!(message.pubkey, signer.public_key().compress());
+        assert!(bid.verify_signature(&spec));
+    }
+
+    #[test]
+    fn electra_header_submission_converts_to_signed_builder_bid() {
+        let spec = ChainSpec::mainnet();
+        let signer = SecretKey::random();
+        let blobs_bundle = blobs_bundle();
+        let submission = SignedHeaderSubmission::Electra(SignedHeaderSubmissionElectra {
+            message: HeaderSubmissionElectra {
+                bid_trace: bid_trace(),
+                execution_payload_header: Default::default(),
+                blobs_bundle: blobs_bundle.clone(),
+                execution_requests: Default::default(),
+            },
+            signature: Signature::empty(),
+        });
+
+        let bid = submission.to_signed_builder_bid(&signer, &spec);
+        let builder_bid::BuilderBid::Electra(message) = &bid.message else {
+            panic!("expected an Electra bid");
+        };
+        assert_eq!(message.blob_kzg_commitments, blobs_bundle.commitments);
+        assert_eq!(message.execution_requests, ExecutionRequests::default());
+        assert_eq!(message.value, bid_trace().value);
+        assert!(bid.verify_signature(&spec));
+    }
+
+    #[test]
+    fn top_bid_update_ssz_round_trips() {
+        let update = TopBidUpdate {
+            timestamp: 1_700_000_000,
+            slot: Slot::new(8_000_000),
+            block_number: 19_000_000,
+            block_hash: ExecutionBlockHash::from_root(Hash256::repeat_byte(1)),
+            parent_hash: ExecutionBlockHash::from_root(Hash256::repeat_byte(2)),
+            builder_pubkey: PublicKeyBytes::deserialize(&[4; 48]).unwrap(),
+            fee_recipient: Address::repeat_byte(3),
+            value: Uint256::from(u128::MAX),
+        };
+        let bytes = update.as_ssz_bytes();
+        assert_eq!(bytes.len(), <TopBidUpdate as Encode>::ssz_fixed_len());
+
+        let decoded = TopBidUpdate::from_ssz_bytes(&bytes).unwrap();
+        assert_eq!(decoded, update);
+    }
+
+    fn hex(byte: u8, len: usize) -> String {
+        format!("0x{}", format!("{:02x}", byte).repeat(len))
+    }
+
+    #[test]
+    fn filtering_is_kebab_case() {
+        assert_eq!(
+            serde_json::to_string(&Filtering::Regional).unwrap(),
+            r#""regional""#
+        );
+        assert_eq!(
+            serde_json::to_string(&Filtering::Global).unwrap(),
+            r#""global""#
+        );
+    }
+
+    #[test]
+    fn validator_preferences_wire_format() {
+        let preferences = ValidatorPreferences {
+            filtering: Filtering::Global,
+            trusted_builders: Some(vec!["builder".to_string()]),
+            header_delay: Some(true),
+            gossip_blobs: None,
+            builder_id: None,
+        };
+        let expected = json!({
+            "filtering": "global",
+            "trusted_builders": ["builder"],
+            "header_delay": true,
+        });
+        assert_eq!(serde_json::to_value(&preferences).unwrap(), expected);
+        assert_eq!(
+            serde_json::from_value::<ValidatorPreferences>(expected).unwrap(),
+            preferences
+        );
+
+        // Relays that predate the optional fields send only the filtering and trusted builders.
+        let minimal: ValidatorPreferences =
+            serde_json::from_value(json!({ "trusted_builders": null })).unwrap();
+        assert_eq!(minimal, ValidatorPreferences::default());
+    }
+
+    #[test]
+    fn bid_trace_v2_with_timestamp_wire_format() {
+        let bid = BidTraceV2WithTimestamp {
+            bid_trace: BidTraceV2 {
+                bid_trace: bid_trace(),
+                block_number: 100,
+                num_tx: 5,
+            },
+            timestamp: 1_700_000_000,
+            timestamp_ms: 1_700_000_000_123,
+        };
+        let expected = json!({
+            "slot": "1",
+            "parent_hash": hex(1, 32),
+            "block_hash": hex(2, 32),
+            "builder_pubkey": hex(0, 48),
+            "proposer_pubkey": hex(0, 48),
+            "proposer_fee_recipient": hex(3, 20),
+            "gas_limit": "30000000",
+            "gas_used": "15000000",
+            "value": "1000000000",
+            "block_number": "100",
+            "num_tx": "5",
+            "timestamp": "1700000000",
+            "timestamp_ms": "1700000000123",
+        });
+        assert_eq!(serde_json::to_value(&bid).unwrap(), expected);
+        assert_eq!(
+            serde_json::from_value::<BidTraceV2WithTimestamp>(expected).unwrap(),
+            bid
+        );
+    }
+
+    #[test]
+    fn bid_trace_v2_with_timestamp_ssz_round_trips() {
+        let bids: Vec<BidTraceV2WithTimestamp> = (0..3)
+            .map(|i| BidTraceV2WithTimestamp {
+                bid_trace: BidTraceV2 {
+                    bid_trace: bid_trace(),
+                    block_number: 100 + i,
+                    num_tx: 5 + i,
+                },
+                timestamp: 1_700_000_000 + i as i64,
+                // Negative timestamps survive as their two's complement.
+                timestamp_ms: -(i as i64) - 1,
+            })
+            .collect();
+
+        let expected_len = <BidTraceV2 as Encode>::ssz_fixed_len() + 16;
+        assert_eq!(
+            <BidTraceV2WithTimestamp as Encode>::ssz_fixed_len(),
+            expected_len
+        );
+        assert_eq!(
+            <BidTraceV2WithTimestamp as Decode>::ssz_fixed_len(),
+            expected_len
+        );
+        assert_eq!(bids[0].ssz_bytes_len(), expected_len);
+
+        let bytes = bids[0].as_ssz_bytes();
+        assert_eq!(bytes.len(), expected_len);
+        assert_eq!(
+            BidTraceV2WithTimestamp::from_ssz_bytes(&bytes).unwrap(),
+            bids[0]
+        );
+
+        // Bid lists are encoded as the concatenation of the fixed-size bids.
+        let bytes = bids.as_ssz_bytes();
+        assert_eq!(bytes.len(), bids.len() * expected_len);
+        assert_eq!(
+            Vec::<BidTraceV2WithTimestamp>::from_ssz_bytes(&bytes).unwrap(),
+            bids
+        );
+    }
+
+    #[test]
+    fn bid_trace_v2_with_timestamp_defaults_missing_timestamps() {
+        let mut json = serde_json::to_value(BidTraceV2 {
+            bid_trace: bid_trace(),
+            block_number: 100,
+            num_tx: 5,
+        })
+        .unwrap();
+        json["timestamp_ms"] = json!("1700000000123");
+
+        let bid: BidTraceV2WithTimestamp = serde_json::from_value(json).unwrap();
+        assert_eq!(bid.timestamp, 0);
+        assert_eq!(bid.timestamp_ms, 1_700_000_000_123);
+    }
+}
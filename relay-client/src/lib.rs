@@ -1,15 +1,27 @@
-pub use ethereum_apis_common::{ContentEncoding, ContentType, ErrorResponse};
+pub use ethereum_apis_common::{
+    ClientConfig, ContentEncoding, ContentType, ErrorResponse, NetworkConfig,
+};
 use futures::{Stream, StreamExt};
 use http::header::InvalidHeaderValue;
 use http::header::CONTENT_ENCODING;
 use http::header::CONTENT_TYPE;
+use http::header::RETRY_AFTER;
 use http::HeaderValue;
 pub use relay_api_types::*;
 use reqwest::Client;
+use reqwest::RequestBuilder;
 use reqwest::Url;
 use serde::Deserialize;
+use ssz::Decode;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
+/// `User-Agent` sent unless overridden through [`ClientConfig::user_agent`].
+pub const DEFAULT_USER_AGENT: &str =
+    concat!("ethereum-apis-relay-client/", env!("CARGO_PKG_VERSION"));
+
 #[derive(Debug)]
 pub enum Error {
     Reqwest(reqwest::Error),
@@ -17,8 +29,45 @@ pub enum Error {
     ServerMessage(ErrorResponse),
     StatusCode(http::StatusCode),
     InvalidUrl(Url),
+    /// The response body exceeded the configured maximum size.
+    ResponseTooLarge {
+        limit: usize,
+    },
     WebSocket(tokio_tungstenite::tungstenite::Error),
     InvalidHeader(InvalidHeaderValue),
+    InvalidSsz(ssz::DecodeError),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Reqwest(e) => write!(f, "request failed: {}", e),
+            Error::InvalidJson(e, text) => write!(f, "invalid JSON response ({}): {}", e, text),
+            Error::ServerMessage(response) => {
+                write!(f, "server returned {}: {}", response.code, response.message)
+            }
+            Error::StatusCode(status) => write!(f, "unexpected status code {}", status),
+            Error::InvalidUrl(url) => write!(f, "invalid base URL {}", url),
+            Error::ResponseTooLarge { limit } => {
+                write!(f, "response body exceeds limit of {} bytes", limit)
+            }
+            Error::WebSocket(e) => write!(f, "websocket error: {}", e),
+            Error::InvalidHeader(e) => write!(f, "invalid header value: {}", e),
+            Error::InvalidSsz(e) => write!(f, "invalid SSZ response: {:?}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Reqwest(e) => Some(e),
+            Error::InvalidJson(e, _) => Some(e),
+            Error::WebSocket(e) => Some(e),
+            Error::InvalidHeader(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<reqwest::Error> for Error {
@@ -39,18 +88,154 @@ impl From<InvalidHeaderValue> for Error {
     }
 }
 
+/// Retry policy applied to the idempotent data API requests.
+///
+/// Requests are retried on connection errors, timeouts and 5xx responses with an exponential
+/// backoff starting at `base_delay`. A 429 response is retried after the delay given by its
+/// `Retry-After` header, if present. Only the delta-seconds form of `Retry-After` is read; an
+/// HTTP-date falls back to the backoff. No single wait exceeds `max_delay`.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    /// Upper bound on the wait before a retry, including waits requested by `Retry-After`.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+/// An item yielded by [`RelayClient::subscribe_top_bids_reconnecting`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TopBidEvent {
+    Update(TopBidUpdate),
+    /// A websocket connection to the relay was established.
+    Connected,
+    /// The websocket connection was closed; updates may be missed until the next `Connected`.
+    Disconnected,
+}
+
+type TopBidStream = Pin<Box<dyn Stream<Item = Result<TopBidUpdate, Error>> + Send>>;
+
 #[derive(Clone)]
 pub struct RelayClient {
     client: Client,
     base_url: Url,
+    retry_policy: RetryPolicy,
+    network_config: Option<Arc<NetworkConfig>>,
+    max_response_size: Option<usize>,
 }
 
 impl RelayClient {
     pub fn new(base_url: Url) -> Self {
+        let client = Self::build_client(&ClientConfig::default())
+            .expect("default client configuration is valid");
+        Self::with_client(base_url, client)
+    }
+
+    /// Creates a client that sends its requests through `client`.
+    pub fn with_client(base_url: Url, client: Client) -> Self {
         Self {
-            client: Client::new(),
+            client,
             base_url,
+            retry_policy: RetryPolicy::default(),
+            network_config: None,
+            max_response_size: None,
+        }
+    }
+
+    /// Creates a client with its `User-Agent`, connection pool and response size limit
+    /// configured by `config`.
+    pub fn with_client_config(base_url: Url, config: &ClientConfig) -> Result<Self, Error> {
+        let mut client = Self::with_client(base_url, Self::build_client(config)?);
+        client.max_response_size = config.max_response_size;
+        Ok(client)
+    }
+
+    fn build_client(config: &ClientConfig) -> Result<Client, reqwest::Error> {
+        let user_agent = config.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT);
+        let mut builder = Client::builder()
+            .user_agent(user_agent)
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .pool_idle_timeout(config.pool_idle_timeout);
+        if config.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        builder.build()
+    }
+
+    pub fn with_network_config(mut self, network_config: NetworkConfig) -> Self {
+        self.network_config = Some(Arc::new(network_config));
+        self
+    }
+
+    pub fn network_config(&self) -> Option<&NetworkConfig> {
+        self.network_config.as_deref()
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    async fn send_with_retry(&self, request: RequestBuilder) -> Result<reqwest::Response, Error> {
+        let mut attempt = 1;
+        loop {
+            let Some(next) = request.try_clone() else {
+                return Ok(request.send().await?);
+            };
+            let can_retry = attempt < self.retry_policy.max_attempts;
+            let backoff = self
+                .retry_policy
+                .base_delay
+                .saturating_mul(2u32.saturating_pow(attempt - 1));
+
+            let delay = match next.send().await {
+                Ok(response)
+                    if can_retry && response.status() == http::StatusCode::TOO_MANY_REQUESTS =>
+                {
+                    response
+                        .headers()
+                        .get(RETRY_AFTER)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(|value| value.parse::<u64>().ok())
+                        .map(Duration::from_secs)
+                        .unwrap_or(backoff)
+                }
+                Ok(response) if can_retry && response.status().is_server_error() => backoff,
+                Ok(response) => return Ok(response),
+                Err(e) if can_retry && (e.is_connect() || e.is_timeout()) => backoff,
+                Err(e) => return Err(e.into()),
+            };
+
+            tokio::time::sleep(delay.min(self.retry_policy.max_delay)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Reads the response body, failing with [`Error::ResponseTooLarge`] as soon as it exceeds
+    /// the configured maximum response size.
+    async fn read_text(&self, mut response: reqwest::Response) -> Result<String, Error> {
+        let Some(limit) = self.max_response_size else {
+            return Ok(response.text().await?);
+        };
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > limit {
+                return Err(Error::ResponseTooLarge { limit });
+            }
+            body.extend_from_slice(&chunk);
         }
+        Ok(String::from_utf8_lossy(&body).into_owned())
     }
 
     async fn build_response<T>(&self, response: reqwest::Response) -> Result<T, Error>
@@ -76,7 +261,7 @@ impl RelayClient {
         );
 
         match content_encoding {
-            ContentEncoding::Gzip => {
+            ContentEncoding::Gzip | ContentEncoding::Deflate | ContentEncoding::Zstd => {
                 response.headers_mut().insert(
                     CONTENT_ENCODING,
                     HeaderValue::from_str(content_encoding.to_string().as_str())?,
@@ -86,11 +271,17 @@ impl RelayClient {
         }
 
         let status = response.status();
-        let text = response.text().await;
+        let text = self.read_text(response).await;
 
         if status.is_success() {
             let text = text?;
-            serde_json::from_str(&text).map_err(|e| Error::InvalidJson(e, text))
+            // An empty body is read as JSON `null` so that unit and optional responses succeed.
+            let json = if text.trim().is_empty() {
+                "null"
+            } else {
+                &text
+            };
+            serde_json::from_str(json).map_err(|e| Error::InvalidJson(e, text))
         } else if let Ok(message) = text {
             Err(Error::ServerMessage(
                 serde_json::from_str(&message).map_err(|e| Error::InvalidJson(e, message))?,
@@ -106,7 +297,7 @@ impl RelayClient {
         body: &SubmitBlockRequest<E>,
         content_type: ContentType,
         content_encoding: ContentEncoding,
-    ) -> Result<(), Error>
+    ) -> Result<SubmitBlockResponse, Error>
     where
         E: EthSpec,
     {
@@ -139,6 +330,17 @@ impl RelayClient {
         self.build_response(response).await
     }
 
+    /// Fetches the forks and optional endpoints the relay supports.
+    pub async fn get_capabilities(&self) -> Result<GetCapabilitiesResponse, Error> {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl(self.base_url.clone()))?
+            .extend(&["relay", "v1", "builder", "capabilities"]);
+        let response = self.client.get(url).send().await?;
+
+        self.build_response(response).await
+    }
+
     pub async fn get_delivered_payloads(
         &self,
         query_params: &GetDeliveredPayloadsQueryParams,
@@ -153,7 +355,9 @@ impl RelayClient {
                 "bidtraces",
                 "proposer_payload_delivered",
             ]);
-        let response = self.client.get(url).query(query_params).send().await?;
+        let response = self
+            .send_with_retry(self.client.get(url).query(query_params))
+            .await?;
 
         self.build_response(response).await
     }
@@ -172,7 +376,9 @@ impl RelayClient {
                 "bidtraces",
                 "builder_blocks_received",
             ]);
-        let response = self.client.get(url).query(query_params).send().await?;
+        let response = self
+            .send_with_retry(self.client.get(url).query(query_params))
+            .await?;
 
         self.build_response(response).await
     }
@@ -185,7 +391,27 @@ impl RelayClient {
         url.path_segments_mut()
             .map_err(|_| Error::InvalidUrl(self.base_url.clone()))?
             .extend(&["relay", "v1", "data", "validator_registration"]);
-        let response = self.client.get(url).query(query_params).send().await?;
+        let response = self
+            .send_with_retry(self.client.get(url).query(query_params))
+            .await?;
+
+        self.build_response(response).await
+    }
+
+    pub async fn get_blobs_bundle<E>(
+        &self,
+        query_params: &GetBlobsBundleQueryParams,
+    ) -> Result<GetBlobsBundleResponse<E>, Error>
+    where
+        E: EthSpec,
+    {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl(self.base_url.clone()))?
+            .extend(&["relay", "v1", "data", "blobs"]);
+        let response = self
+            .send_with_retry(self.client.get(url).query(query_params))
+            .await?;
 
         self.build_response(response).await
     }
@@ -196,7 +422,7 @@ impl RelayClient {
         body: &SignedHeaderSubmission<E>,
         content_type: ContentType,
         content_encoding: ContentEncoding,
-    ) -> Result<(), Error>
+    ) -> Result<SubmitBlockResponse, Error>
     where
         E: EthSpec,
     {
@@ -222,7 +448,7 @@ impl RelayClient {
         body: &SubmitBlockRequest<E>,
         content_type: ContentType,
         content_encoding: ContentEncoding,
-    ) -> Result<(), Error>
+    ) -> Result<SubmitBlockResponse, Error>
     where
         E: EthSpec,
     {
@@ -260,9 +486,21 @@ impl RelayClient {
 
     pub async fn subscribe_top_bids(
         &self,
+    ) -> Result<impl Stream<Item = Result<TopBidUpdate, Error>>, Error> {
+        self.subscribe_top_bids_with_format(TopBidsFormat::Json)
+            .await
+    }
+
+    /// Subscribes to top bid updates, asking the relay to send them in the given `format`.
+    pub async fn subscribe_top_bids_with_format(
+        &self,
+        format: TopBidsFormat,
     ) -> Result<impl Stream<Item = Result<TopBidUpdate, Error>>, Error> {
         let mut url = self.base_url.clone();
         url.set_path("/relay/v1/builder/top_bids");
+        if let TopBidsFormat::Ssz = format {
+            url.query_pairs_mut().append_pair("format", "ssz");
+        }
 
         let ws_scheme = match url.scheme() {
             "http" => "ws",
@@ -283,16 +521,53 @@ impl RelayClient {
                     Ok(update) => Some(Ok(update)),
                     Err(e) => Some(Err(Error::InvalidJson(e, text))),
                 },
-                Ok(Message::Binary(bin)) => match serde_json::from_slice::<TopBidUpdate>(&bin) {
+                Ok(Message::Binary(bin)) => match TopBidUpdate::from_ssz_bytes(&bin) {
                     Ok(update) => Some(Ok(update)),
-                    Err(e) => {
-                        let text = String::from_utf8_lossy(&bin).to_string();
-                        Some(Err(Error::InvalidJson(e, text)))
-                    }
+                    Err(e) => Some(Err(Error::InvalidSsz(e))),
                 },
                 _ => None, // Ignore other message types
             }
         });
         Ok(stream)
     }
+
+    /// Subscribes to top bid updates, reconnecting after `reconnect_delay` whenever the
+    /// connection fails or is closed.
+    ///
+    /// Connection failures and undecodable messages are yielded as errors without ending the
+    /// stream.
+    pub fn subscribe_top_bids_reconnecting(
+        &self,
+        format: TopBidsFormat,
+        reconnect_delay: Duration,
+    ) -> impl Stream<Item = Result<TopBidEvent, Error>> {
+        let state: (RelayClient, Option<TopBidStream>, bool) = (self.clone(), None, true);
+
+        futures::stream::unfold(state, move |(client, mut current, mut first)| async move {
+            let Some(stream) = current.as_mut() else {
+                if !first {
+                    tokio::time::sleep(reconnect_delay).await;
+                }
+                first = false;
+
+                return match client.subscribe_top_bids_with_format(format).await {
+                    Ok(stream) => {
+                        let stream: TopBidStream = Box::pin(stream);
+                        Some((Ok(TopBidEvent::Connected), (client, Some(stream), first)))
+                    }
+                    Err(e) => Some((Err(e), (client, None, first))),
+                };
+            };
+
+            let event = match stream.next().await {
+                Some(Ok(update)) => Ok(TopBidEvent::Update(update)),
+                Some(Err(e)) => Err(e),
+                None => {
+                    current = None;
+                    Ok(TopBidEvent::Disconnected)
+                }
+            };
+            Some((event, (client, current, first)))
+        })
+    }
 }
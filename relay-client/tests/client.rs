@@ -0,0 +1,146 @@
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{http::StatusCode, routing::get, Json, Router};
+use relay_client::{
+    Address, BidTraceV1, BidTraceV2WithTimestamp, Blob, BlobsBundle, ContentEncoding, ContentType,
+    ExecutionBlockHash, ExecutionPayloadDeneb, ForkName, GetBlobsBundleQueryParams,
+    GetReceivedBidsQueryParams, Hash256, KzgCommitment, KzgProof, MainnetEthSpec, PublicKeyBytes,
+    RelayClient, RetryPolicy, Signature, Slot, SubmitBlockQueryParams, SubmitBlockRequest,
+    SubmitBlockRequestDeneb, Uint256,
+};
+use relay_server::mock::MockRelay;
+use reqwest::Url;
+use tokio::net::TcpListener;
+
+type E = MainnetEthSpec;
+
+/// Serves `router` on an ephemeral local port, returning its base URL.
+async fn serve(router: Router) -> Url {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap();
+    });
+    url
+}
+
+fn deneb_submission(blobs_bundle: BlobsBundle<E>) -> SubmitBlockRequest<E> {
+    let execution_payload = ExecutionPayloadDeneb::<E> {
+        parent_hash: ExecutionBlockHash::from_root(Hash256::repeat_byte(1)),
+        block_hash: ExecutionBlockHash::from_root(Hash256::repeat_byte(2)),
+        block_number: 100,
+        gas_limit: 30_000_000,
+        gas_used: 15_000_000,
+        ..Default::default()
+    };
+    let message = BidTraceV1 {
+        slot: Slot::new(1),
+        parent_hash: execution_payload.parent_hash,
+        block_hash: execution_payload.block_hash,
+        builder_pubkey: PublicKeyBytes::empty(),
+        proposer_pubkey: PublicKeyBytes::empty(),
+        proposer_fee_recipient: Address::repeat_byte(3),
+        gas_limit: execution_payload.gas_limit,
+        gas_used: execution_payload.gas_used,
+        value: Uint256::from(1_000_000_000u64),
+    };
+    SubmitBlockRequest::Deneb(SubmitBlockRequestDeneb {
+        message,
+        execution_payload,
+        signature: Signature::empty(),
+        blobs_bundle,
+    })
+}
+
+#[tokio::test]
+async fn relay_client_smoke_test_against_mock_relay() {
+    let relay = Arc::new(MockRelay::<E>::new());
+    let url = serve(relay_server::server::new::<_, MockRelay<E>, E>(
+        relay.clone(),
+    ))
+    .await;
+    let client = RelayClient::new(url);
+
+    let capabilities = client.get_capabilities().await.unwrap();
+    assert!(capabilities.supports_fork(ForkName::Deneb));
+    assert!(client.get_validators::<E>().await.unwrap().is_empty());
+
+    let blobs_bundle = BlobsBundle::<E> {
+        commitments: vec![KzgCommitment::empty_for_testing()].into(),
+        proofs: vec![KzgProof::empty()].into(),
+        blobs: vec![Blob::<E>::default()].into(),
+    };
+    let submission = deneb_submission(blobs_bundle.clone());
+    let response = client
+        .submit_block(
+            &SubmitBlockQueryParams {
+                cancellations: None,
+            },
+            &submission,
+            ContentType::Json,
+            ContentEncoding::None,
+        )
+        .await
+        .unwrap();
+    assert!(!response.is_duplicate());
+    assert_eq!(relay.blocks().len(), 1);
+
+    let bids = client
+        .get_received_bids(&GetReceivedBidsQueryParams::default())
+        .await
+        .unwrap();
+    assert_eq!(bids.len(), 1);
+    assert_eq!(&bids[0].bid_trace.bid_trace, submission.message());
+    assert_eq!(bids[0].bid_trace.block_number, 100);
+
+    let served = client
+        .get_blobs_bundle::<E>(&GetBlobsBundleQueryParams {
+            slot: submission.message().slot,
+            block_hash: submission.message().block_hash,
+        })
+        .await
+        .unwrap();
+    assert_eq!(served, blobs_bundle);
+}
+
+#[tokio::test]
+async fn data_requests_retry_until_success() {
+    // Fails the first two requests with a 503.
+    let attempts = Arc::new(AtomicU32::new(0));
+    let router = Router::new().route(
+        "/relay/v1/data/bidtraces/builder_blocks_received",
+        get({
+            let attempts = attempts.clone();
+            move || {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if attempt < 2 {
+                        Err(StatusCode::SERVICE_UNAVAILABLE)
+                    } else {
+                        Ok(Json(Vec::<BidTraceV2WithTimestamp>::new()))
+                    }
+                }
+            }
+        }),
+    );
+    let client = RelayClient::new(serve(router).await).with_retry_policy(RetryPolicy {
+        max_attempts: 3,
+        base_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(10),
+    });
+
+    let bids = client
+        .get_received_bids(&GetReceivedBidsQueryParams::default())
+        .await
+        .unwrap();
+    assert!(bids.is_empty());
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
@@ -4,8 +4,8 @@ use async_trait::async_trait;
 use ethereum_apis_common::ErrorResponse;
 use futures::Stream;
 use relay_api_types::{
-    EthSpec, GetValidatorsResponse, SignedCancellation, SignedHeaderSubmission,
-    SubmitBlockQueryParams, SubmitBlockRequest, TopBidUpdate,
+    EthSpec, GetCapabilitiesResponse, GetValidatorsResponse, SignedCancellation,
+    SignedHeaderSubmission, SubmitBlockQueryParams, SubmitBlockRequest, TopBidUpdate,
 };
 
 /// Builder
@@ -53,4 +53,15 @@ pub trait Builder<E: EthSpec> {
     async fn get_top_bids(
         &self,
     ) -> Result<Pin<Box<dyn Stream<Item = TopBidUpdate> + Send>>, ErrorResponse>;
+
+    /// Get the forks and optional endpoints supported by the relay.
+    ///
+    /// GetCapabilities - GET /relay/v1/builder/capabilities
+    async fn get_capabilities(&self) -> Result<GetCapabilitiesResponse, ErrorResponse> {
+        Err(ErrorResponse {
+            code: 404,
+            message: "capabilities are not advertised by this relay".to_string(),
+            stacktraces: None,
+        })
+    }
 }
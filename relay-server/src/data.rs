@@ -1,14 +1,15 @@
 use async_trait::async_trait;
 use ethereum_apis_common::ErrorResponse;
 use relay_api_types::{
-    GetDeliveredPayloadsQueryParams, GetDeliveredPayloadsResponse, GetReceivedBidsQueryParams,
-    GetReceivedBidsResponse, GetValidatorRegistrationQueryParams, GetValidatorRegistrationResponse,
+    EthSpec, GetBlobsBundleQueryParams, GetBlobsBundleResponse, GetDeliveredPayloadsQueryParams,
+    GetDeliveredPayloadsResponse, GetReceivedBidsQueryParams, GetReceivedBidsResponse,
+    GetValidatorRegistrationQueryParams, GetValidatorRegistrationResponse,
 };
 
 /// Data
 #[async_trait]
 #[allow(clippy::ptr_arg)]
-pub trait Data {
+pub trait Data<E: EthSpec> {
     /// Get payloads that were delivered to proposers..
     ///
     /// GetDeliveredPayloads - GET /relay/v1/data/bidtraces/proposer_payload_delivered
@@ -32,4 +33,23 @@ pub trait Data {
         &self,
         query_params: GetValidatorRegistrationQueryParams,
     ) -> Result<GetValidatorRegistrationResponse, ErrorResponse>;
+
+    /// Get the blobs bundle submitted with a delivered payload.
+    ///
+    /// Relays that don't retain blobs can rely on the default, which responds with a `404`.
+    ///
+    /// GetBlobsBundle - GET /relay/v1/data/blobs
+    async fn get_blobs_bundle(
+        &self,
+        query_params: GetBlobsBundleQueryParams,
+    ) -> Result<GetBlobsBundleResponse<E>, ErrorResponse> {
+        Err(ErrorResponse {
+            code: 404,
+            message: format!(
+                "no blobs bundle for slot {} with block hash {}",
+                query_params.slot, query_params.block_hash
+            ),
+            stacktraces: None,
+        })
+    }
 }
@@ -2,4 +2,7 @@ pub use relay_api_types::*;
 
 pub mod builder;
 pub mod data;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod optimistic_v2;
 pub mod server;
@@ -0,0 +1,505 @@
+//! In-memory relay for integration testing `RelayClient` and other relay consumers.
+//!
+//! Submitted blocks and headers are recorded as received bids, served back through the data
+//! endpoints and broadcast as top bid updates. Alternatively the top bids stream can be driven
+//! by a seeded [`TopBidGenerator`].
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use ethereum_apis_common::ErrorResponse;
+use futures::Stream;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use relay_api_types::{
+    Address, BidTraceV1, BidTraceV2, BidTraceV2WithTimestamp, EthSpec, ExecutionBlockHash,
+    ForkName, GetBlobsBundleQueryParams, GetBlobsBundleResponse, GetCapabilitiesResponse,
+    GetDeliveredPayloadsQueryParams, GetDeliveredPayloadsResponse, GetReceivedBidsQueryParams,
+    GetReceivedBidsResponse, GetValidatorRegistrationQueryParams, GetValidatorRegistrationResponse,
+    GetValidatorsResponse, Hash256, OrderBy, PublicKeyBytes, RelayCapabilities, SignedCancellation,
+    SignedHeaderSubmission, SignedValidatorRegistrationData, Slot, SubmitBlockQueryParams,
+    SubmitBlockRequest, TopBidUpdate, Uint256,
+};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::{builder::Builder, data::Data, optimistic_v2::OptimisticV2};
+
+/// Number of top bid updates buffered for subscribers.
+const TOP_BIDS_CAPACITY: usize = 64;
+
+/// Number of updates a [`TopBidGenerator`] emits before moving to the next slot.
+const GENERATED_UPDATES_PER_SLOT: u64 = 4;
+
+/// Deterministic source of synthetic top bid updates.
+///
+/// Each slot gets [`GENERATED_UPDATES_PER_SLOT`] updates with strictly increasing values, after
+/// which the slot and block number advance and the value starts again from zero. The same seed
+/// always yields the same sequence.
+pub struct TopBidGenerator {
+    interval: Duration,
+    rng: StdRng,
+    slot: Slot,
+    updates_in_slot: u64,
+    parent_hash: ExecutionBlockHash,
+    value: Uint256,
+}
+
+impl TopBidGenerator {
+    pub fn new(interval: Duration, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let parent_hash = random_block_hash(&mut rng);
+        Self {
+            interval,
+            rng,
+            slot: Slot::new(1),
+            updates_in_slot: 0,
+            parent_hash,
+            value: Uint256::from(0u64),
+        }
+    }
+
+    /// Emits the generated updates, one every `interval`.
+    pub fn into_stream(self) -> impl Stream<Item = TopBidUpdate> + Send {
+        let interval = tokio::time::interval(self.interval);
+        futures::stream::unfold(
+            (self, interval),
+            |(mut generator, mut interval)| async move {
+                interval.tick().await;
+                let update = generator.next()?;
+                Some((update, (generator, interval)))
+            },
+        )
+    }
+}
+
+impl Iterator for TopBidGenerator {
+    type Item = TopBidUpdate;
+
+    fn next(&mut self) -> Option<TopBidUpdate> {
+        if self.updates_in_slot == GENERATED_UPDATES_PER_SLOT {
+            self.slot += 1;
+            self.updates_in_slot = 0;
+            self.parent_hash = random_block_hash(&mut self.rng);
+            self.value = Uint256::from(0u64);
+        }
+        self.updates_in_slot += 1;
+        self.value += Uint256::from(self.rng.gen_range(1..=1_000_000_000u64));
+
+        Some(TopBidUpdate {
+            timestamp: self.slot.as_u64() * 12,
+            slot: self.slot,
+            block_number: self.slot.as_u64(),
+            block_hash: random_block_hash(&mut self.rng),
+            parent_hash: self.parent_hash,
+            builder_pubkey: PublicKeyBytes::empty(),
+            fee_recipient: Address::from_slice(&self.rng.gen::<[u8; 20]>()),
+            value: self.value,
+        })
+    }
+}
+
+fn random_block_hash(rng: &mut StdRng) -> ExecutionBlockHash {
+    ExecutionBlockHash::from_root(Hash256::from_slice(&rng.gen::<[u8; 32]>()))
+}
+
+pub struct MockRelay<E: EthSpec> {
+    validators: Mutex<GetValidatorsResponse>,
+    registrations: Mutex<HashMap<PublicKeyBytes, SignedValidatorRegistrationData>>,
+    blocks: Mutex<Vec<SubmitBlockRequest<E>>>,
+    headers: Mutex<Vec<SignedHeaderSubmission<E>>>,
+    received_bids: Mutex<Vec<BidTraceV2WithTimestamp>>,
+    delivered_payloads: Mutex<Vec<BidTraceV2>>,
+    top_bids: broadcast::Sender<TopBidUpdate>,
+    top_bid_generator: Option<(Duration, u64)>,
+    next_submission_error: Mutex<Option<ErrorResponse>>,
+}
+
+impl<E: EthSpec> Default for MockRelay<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: EthSpec> MockRelay<E> {
+    pub fn new() -> Self {
+        let (top_bids, _) = broadcast::channel(TOP_BIDS_CAPACITY);
+        Self {
+            validators: Mutex::new(Vec::new()),
+            registrations: Mutex::new(HashMap::new()),
+            blocks: Mutex::new(Vec::new()),
+            headers: Mutex::new(Vec::new()),
+            received_bids: Mutex::new(Vec::new()),
+            delivered_payloads: Mutex::new(Vec::new()),
+            top_bids,
+            top_bid_generator: None,
+            next_submission_error: Mutex::new(None),
+        }
+    }
+
+    /// Creates a relay whose top bids subscribers each receive the sequence of a
+    /// [`TopBidGenerator`] with the given `interval` and `seed`, instead of updates for
+    /// submitted bids.
+    pub fn with_top_bid_generator(interval: Duration, seed: u64) -> Self {
+        Self {
+            top_bid_generator: Some((interval, seed)),
+            ..Self::new()
+        }
+    }
+
+    /// Sets the proposer duties returned by `get_validators`, also making their registrations
+    /// available through `get_validator_registration`.
+    pub fn set_validators(&self, validators: GetValidatorsResponse) {
+        let mut registrations = self.registrations.lock().unwrap();
+        for validator in &validators {
+            registrations.insert(validator.entry.message.pubkey, validator.entry.clone());
+        }
+        *self.validators.lock().unwrap() = validators;
+    }
+
+    /// Marks a bid as delivered to the proposer so it is served by `get_delivered_payloads`.
+    pub fn record_delivered_payload(&self, bid_trace: BidTraceV2) {
+        self.delivered_payloads.lock().unwrap().push(bid_trace);
+    }
+
+    /// Broadcasts `update` to all top bids subscribers.
+    pub fn send_top_bid(&self, update: TopBidUpdate) {
+        // Sending only fails when nobody is subscribed.
+        let _ = self.top_bids.send(update);
+    }
+
+    /// Fails the next block submission with `error` instead of recording it.
+    pub fn fail_next_submission(&self, error: ErrorResponse) {
+        *self.next_submission_error.lock().unwrap() = Some(error);
+    }
+
+    pub fn blocks(&self) -> Vec<SubmitBlockRequest<E>> {
+        self.blocks.lock().unwrap().clone()
+    }
+
+    pub fn headers(&self) -> Vec<SignedHeaderSubmission<E>> {
+        self.headers.lock().unwrap().clone()
+    }
+
+    fn record_bid(&self, bid_trace: BidTraceV1, block_number: u64, num_tx: u64) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        self.send_top_bid(TopBidUpdate {
+            timestamp: now.as_secs(),
+            slot: bid_trace.slot,
+            block_number,
+            block_hash: bid_trace.block_hash,
+            parent_hash: bid_trace.parent_hash,
+            builder_pubkey: bid_trace.builder_pubkey,
+            fee_recipient: bid_trace.proposer_fee_recipient,
+            value: bid_trace.value,
+        });
+
+        self.received_bids
+            .lock()
+            .unwrap()
+            .push(BidTraceV2WithTimestamp {
+                bid_trace: BidTraceV2 {
+                    bid_trace,
+                    block_number,
+                    num_tx,
+                },
+                timestamp: now.as_secs() as i64,
+                timestamp_ms: now.as_millis() as i64,
+            });
+    }
+}
+
+fn block_number_and_num_tx<E: EthSpec>(body: &SubmitBlockRequest<E>) -> (u64, u64) {
+    match body {
+        SubmitBlockRequest::Bellatrix(body) => (
+            body.execution_payload.block_number,
+            body.execution_payload.transactions.len() as u64,
+        ),
+        SubmitBlockRequest::Capella(body) => (
+            body.execution_payload.block_number,
+            body.execution_payload.transactions.len() as u64,
+        ),
+        SubmitBlockRequest::Deneb(body) => (
+            body.execution_payload.block_number,
+            body.execution_payload.transactions.len() as u64,
+        ),
+        SubmitBlockRequest::Electra(body) => (
+            body.execution_payload.block_number,
+            body.execution_payload.transactions.len() as u64,
+        ),
+    }
+}
+
+fn header_bid_trace_and_block_number<E: EthSpec>(
+    body: &SignedHeaderSubmission<E>,
+) -> (BidTraceV1, u64) {
+    match body {
+        SignedHeaderSubmission::Bellatrix(body) => (
+            body.message.bid_trace.clone(),
+            body.message.execution_payload_header.block_number,
+        ),
+        SignedHeaderSubmission::Capella(body) => (
+            body.message.bid_trace.clone(),
+            body.message.execution_payload_header.block_number,
+        ),
+        SignedHeaderSubmission::Deneb(body) => (
+            body.message.bid_trace.clone(),
+            body.message.execution_payload_header.block_number,
+        ),
+        SignedHeaderSubmission::Electra(body) => (
+            body.message.bid_trace.clone(),
+            body.message.execution_payload_header.block_number,
+        ),
+    }
+}
+
+fn sort_and_limit<T>(
+    mut bids: Vec<T>,
+    order_by: Option<&OrderBy>,
+    limit: Option<u64>,
+    bid_trace: impl Fn(&T) -> &BidTraceV2,
+) -> Vec<T> {
+    match order_by {
+        Some(OrderBy::Value) => bids.sort_by(|a, b| {
+            bid_trace(a)
+                .bid_trace
+                .value
+                .cmp(&bid_trace(b).bid_trace.value)
+        }),
+        Some(OrderBy::NegativeValue) => bids.sort_by(|a, b| {
+            bid_trace(b)
+                .bid_trace
+                .value
+                .cmp(&bid_trace(a).bid_trace.value)
+        }),
+        // Most recent first.
+        None => bids.reverse(),
+    }
+    if let Some(limit) = limit {
+        bids.truncate(limit as usize);
+    }
+    bids
+}
+
+#[async_trait]
+impl<E: EthSpec> Builder<E> for MockRelay<E> {
+    async fn get_validators(&self) -> Result<GetValidatorsResponse, ErrorResponse> {
+        Ok(self.validators.lock().unwrap().clone())
+    }
+
+    async fn submit_block(
+        &self,
+        _query_params: SubmitBlockQueryParams,
+        body: SubmitBlockRequest<E>,
+    ) -> Result<(), ErrorResponse> {
+        if let Some(error) = self.next_submission_error.lock().unwrap().take() {
+            return Err(error);
+        }
+        let (block_number, num_tx) = block_number_and_num_tx(&body);
+        self.record_bid(body.message().clone(), block_number, num_tx);
+        self.blocks.lock().unwrap().push(body);
+        Ok(())
+    }
+
+    async fn submit_header(
+        &self,
+        _query_params: SubmitBlockQueryParams,
+        body: SignedHeaderSubmission<E>,
+    ) -> Result<(), ErrorResponse> {
+        // Headers carry no transactions, so they are recorded with `num_tx` of zero.
+        let (bid_trace, block_number) = header_bid_trace_and_block_number(&body);
+        self.record_bid(bid_trace, block_number, 0);
+        self.headers.lock().unwrap().push(body);
+        Ok(())
+    }
+
+    async fn submit_block_optimistic_v2(
+        &self,
+        query_params: SubmitBlockQueryParams,
+        body: SubmitBlockRequest<E>,
+    ) -> Result<(), ErrorResponse> {
+        self.submit_block(query_params, body).await
+    }
+
+    async fn submit_cancellation(&self, body: SignedCancellation) -> Result<(), ErrorResponse> {
+        let cancellation = body.message;
+        self.received_bids.lock().unwrap().retain(|bid| {
+            let bid = &bid.bid_trace.bid_trace;
+            bid.slot.as_u64() != cancellation.slot
+                || bid.builder_pubkey != cancellation.builder_public_key
+        });
+        Ok(())
+    }
+
+    async fn get_top_bids(
+        &self,
+    ) -> Result<Pin<Box<dyn Stream<Item = TopBidUpdate> + Send>>, ErrorResponse> {
+        if let Some((interval, seed)) = self.top_bid_generator {
+            return Ok(Box::pin(TopBidGenerator::new(interval, seed).into_stream()));
+        }
+
+        let receiver = self.top_bids.subscribe();
+        let stream = futures::stream::unfold(receiver, |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(update) => return Some((update, receiver)),
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+        Ok(Box::pin(stream))
+    }
+
+    async fn get_capabilities(&self) -> Result<GetCapabilitiesResponse, ErrorResponse> {
+        Ok(RelayCapabilities {
+            forks: vec![
+                ForkName::Bellatrix,
+                ForkName::Capella,
+                ForkName::Deneb,
+                ForkName::Electra,
+            ],
+            optimistic_v2: true,
+            ssz_submissions: true,
+        })
+    }
+}
+
+#[async_trait]
+impl<E: EthSpec> OptimisticV2<E> for MockRelay<E> {
+    async fn submit_header_v2(
+        &self,
+        query_params: SubmitBlockQueryParams,
+        body: SignedHeaderSubmission<E>,
+    ) -> Result<(), ErrorResponse> {
+        Builder::submit_header(self, query_params, body).await
+    }
+
+    async fn submit_payload_v2(
+        &self,
+        _query_params: SubmitBlockQueryParams,
+        body: SubmitBlockRequest<E>,
+    ) -> Result<(), ErrorResponse> {
+        // The bid was already recorded when its header was submitted.
+        self.blocks.lock().unwrap().push(body);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<E: EthSpec> Data<E> for MockRelay<E> {
+    async fn get_delivered_payloads(
+        &self,
+        query_params: GetDeliveredPayloadsQueryParams,
+    ) -> Result<GetDeliveredPayloadsResponse, ErrorResponse> {
+        let payloads = self
+            .delivered_payloads
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|payload| {
+                let bid = &payload.bid_trace;
+                query_params.slot.is_none_or(|slot| bid.slot == slot)
+                    && query_params.cursor.is_none_or(|cursor| bid.slot <= cursor)
+                    && query_params
+                        .block_hash
+                        .is_none_or(|block_hash| bid.block_hash == block_hash)
+                    && query_params
+                        .block_number
+                        .is_none_or(|number| payload.block_number == number.value)
+                    && query_params
+                        .proposer_pubkey
+                        .is_none_or(|pubkey| bid.proposer_pubkey == pubkey)
+                    && query_params
+                        .builder_pubkey
+                        .is_none_or(|pubkey| bid.builder_pubkey == pubkey)
+            })
+            .cloned()
+            .collect();
+
+        Ok(sort_and_limit(
+            payloads,
+            query_params.order_by.as_ref(),
+            query_params.limit.map(|limit| limit.as_u64()),
+            |payload| payload,
+        ))
+    }
+
+    async fn get_received_bids(
+        &self,
+        query_params: GetReceivedBidsQueryParams,
+    ) -> Result<GetReceivedBidsResponse, ErrorResponse> {
+        let bids = self
+            .received_bids
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|received| {
+                let bid = &received.bid_trace.bid_trace;
+                query_params.slot.is_none_or(|slot| bid.slot == slot)
+                    && query_params
+                        .block_hash
+                        .is_none_or(|block_hash| bid.block_hash == block_hash)
+                    && query_params
+                        .block_number
+                        .is_none_or(|number| received.bid_trace.block_number == number.value)
+                    && query_params
+                        .builder_pubkey
+                        .is_none_or(|pubkey| bid.builder_pubkey == pubkey)
+            })
+            .cloned()
+            .collect();
+
+        Ok(sort_and_limit(
+            bids,
+            query_params.order_by.as_ref(),
+            query_params.limit.map(|limit| limit.as_u64()),
+            |received| &received.bid_trace,
+        ))
+    }
+
+    async fn get_validator_registration(
+        &self,
+        query_params: GetValidatorRegistrationQueryParams,
+    ) -> Result<GetValidatorRegistrationResponse, ErrorResponse> {
+        self.registrations
+            .lock()
+            .unwrap()
+            .get(&query_params.pubkey)
+            .cloned()
+            .ok_or_else(|| ErrorResponse {
+                code: 400,
+                message: "no registration found for validator".to_string(),
+                stacktraces: None,
+            })
+    }
+
+    async fn get_blobs_bundle(
+        &self,
+        query_params: GetBlobsBundleQueryParams,
+    ) -> Result<GetBlobsBundleResponse<E>, ErrorResponse> {
+        self.blocks
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|block| {
+                let bid_trace = block.message();
+                bid_trace.slot == query_params.slot
+                    && bid_trace.block_hash == query_params.block_hash
+            })
+            // Only forks with blobs have a bundle.
+            .find_map(|block| block.blobs_bundle().ok().cloned())
+            .ok_or_else(|| ErrorResponse {
+                code: 404,
+                message: format!(
+                    "no blobs bundle for slot {} with block hash {}",
+                    query_params.slot, query_params.block_hash
+                ),
+                stacktraces: None,
+            })
+    }
+}
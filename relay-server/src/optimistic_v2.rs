@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use ethereum_apis_common::ErrorResponse;
+use relay_api_types::{
+    EthSpec, SignedHeaderSubmission, SubmitBlockQueryParams, SubmitBlockRequest,
+};
+
+/// Optimistic V2
+#[async_trait]
+pub trait OptimisticV2<E: EthSpec> {
+    /// Submit a new block header ahead of its payload.
+    ///
+    /// Only served by the standalone optimistic v2 router; the combined router passes headers
+    /// to [`Builder::submit_header`](crate::builder::Builder::submit_header) instead.
+    ///
+    /// SubmitHeader - POST /relay/v1/builder/headers
+    async fn submit_header_v2(
+        &self,
+        _query_params: SubmitBlockQueryParams,
+        _body: SignedHeaderSubmission<E>,
+    ) -> Result<(), ErrorResponse> {
+        Err(ErrorResponse {
+            code: 404,
+            message: "optimistic v2 header submission is not supported".to_string(),
+            stacktraces: None,
+        })
+    }
+
+    /// Submit the full payload for a header previously submitted to the relay.
+    ///
+    /// Only called for payloads whose slot, builder pubkey and block hash match a pending
+    /// header. Relays without optimistic v2 support can rely on the default, which responds
+    /// with a `404`.
+    ///
+    /// SubmitPayloadV2 - POST /relay/v1/builder/payloads_optimistic_v2
+    async fn submit_payload_v2(
+        &self,
+        _query_params: SubmitBlockQueryParams,
+        _body: SubmitBlockRequest<E>,
+    ) -> Result<(), ErrorResponse> {
+        Err(ErrorResponse {
+            code: 404,
+            message: "optimistic v2 payload submission is not supported".to_string(),
+            stacktraces: None,
+        })
+    }
+}
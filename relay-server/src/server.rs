@@ -1,30 +1,355 @@
-use crate::{builder::Builder, data::Data};
+use crate::{builder::Builder, data::Data, optimistic_v2::OptimisticV2};
 use axum::extract::connect_info::ConnectInfo;
 use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
 use axum::{
     body::Body,
     extract::{Query, State},
-    response::{IntoResponse, Response},
+    middleware,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     routing::{get, post},
-    Router,
+    Extension, Router,
+};
+use ethereum_apis_common::{
+    build_response, build_unversioned_ssz_response, method_not_allowed, negotiate_content_type,
+    not_found, ContentType, ErrorResponse, JsonOrSszMaybeGzipped, JsonOrSszWithFork,
 };
-use ethereum_apis_common::{build_response, JsonOrSszMaybeGzipped};
 use futures::{sink::SinkExt, stream::StreamExt};
-use http::StatusCode;
+use http::header::{ACCEPT, CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH, RETRY_AFTER, VARY};
+use http::{HeaderMap, HeaderValue, StatusCode};
 use relay_api_types::{
-    EthSpec, GetDeliveredPayloadsQueryParams, GetReceivedBidsQueryParams,
-    GetValidatorRegistrationQueryParams, SignedCancellation, SignedHeaderSubmission,
-    SubmitBlockQueryParams, SubmitBlockRequest,
+    BidTraceV1, EthSpec, ExecutionBlockHash, GetBlobsBundleQueryParams,
+    GetDeliveredPayloadsQueryParams, GetReceivedBidsQueryParams,
+    GetValidatorRegistrationQueryParams, PublicKeyBytes, SignedCancellation,
+    SignedHeaderSubmission, Slot, SubmitBlockQueryParams, SubmitBlockRequest, SubmitBlockResponse,
+    TopBidUpdate, TopBidsFormat, TopBidsQueryParams,
 };
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use ssz::Encode;
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::future::Future;
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Tunables for the relay API server.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// Interval between pings sent to top bids websocket subscribers. Raised to 1ms if zero.
+    pub ws_ping_interval: Duration,
+    /// How long after a ping to wait for a pong before dropping the subscriber.
+    pub ws_pong_timeout: Duration,
+    /// Number of top bid updates buffered per subscriber before the oldest are dropped. Raised
+    /// to 1 if zero.
+    pub ws_buffer_size: usize,
+    /// `max-age` advertised in the `Cache-Control` header of bid trace data responses.
+    pub data_cache_max_age: Duration,
+    /// Upper bound on the `limit` of bid trace data requests. Larger limits are clamped and
+    /// requests without a limit are given this one.
+    pub data_max_limit: u64,
+    /// Acknowledge repeated block submissions with the same slot, builder and block hash
+    /// without passing them to the API implementation again.
+    pub deduplicate_submissions: bool,
+    /// Per-builder submission quota for the block and header submission endpoints.
+    pub rate_limit: Option<SubmissionRateLimit>,
+    /// How long the API implementation may take to process a block or header submission
+    /// before the request fails with `504 Gateway Timeout`.
+    pub submission_timeout: Option<Duration>,
+    /// Wall clock used to reject block and header submissions for slots that haven't started.
+    pub slot_clock: SlotClock,
+}
+
+#[derive(Debug, Clone)]
+pub struct SubmissionRateLimit {
+    /// Maximum number of submissions accepted from a single builder pubkey in one slot.
+    pub max_submissions_per_slot: u32,
+    /// Value of the `Retry-After` header sent with `429 Too Many Requests`.
+    pub retry_after: Duration,
+}
+
+/// Maps the wall clock to slots.
+#[derive(Debug, Clone, Copy)]
+pub struct SlotClock {
+    /// Start of slot 0, as a duration since the Unix epoch.
+    pub genesis_time: Duration,
+    /// Length of a slot. A zero duration is treated as 1ms.
+    pub slot_duration: Duration,
+}
+
+impl SlotClock {
+    pub fn mainnet() -> Self {
+        Self {
+            genesis_time: Duration::from_secs(1_606_824_023),
+            slot_duration: Duration::from_secs(12),
+        }
+    }
+
+    /// Returns the current slot, or slot 0 before genesis.
+    pub fn now(&self) -> Slot {
+        let since_genesis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .saturating_sub(self.genesis_time);
+        let slot_millis = self.slot_duration.as_millis().max(1);
+        Slot::new((since_genesis.as_millis() / slot_millis) as u64)
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            ws_ping_interval: Duration::from_secs(30),
+            ws_pong_timeout: Duration::from_secs(10),
+            ws_buffer_size: 16,
+            data_cache_max_age: Duration::from_secs(12),
+            data_max_limit: 200,
+            deduplicate_submissions: false,
+            rate_limit: None,
+            submission_timeout: None,
+            slot_clock: SlotClock::mainnet(),
+        }
+    }
+}
+
+/// Smallest websocket ping interval, since a zero interval would panic.
+const MIN_WS_PING_INTERVAL: Duration = Duration::from_millis(1);
+
+impl ServerConfig {
+    /// Raises values that would panic on every websocket upgrade to their minimum.
+    fn clamped(mut self) -> Self {
+        self.ws_ping_interval = self.ws_ping_interval.max(MIN_WS_PING_INTERVAL);
+        self.ws_buffer_size = self.ws_buffer_size.max(1);
+        self
+    }
+}
+
+/// Submission counts per slot and builder pubkey.
+#[derive(Default)]
+struct SubmissionRateLimiter {
+    counts: Mutex<SubmissionCounts>,
+}
+
+#[derive(Default)]
+struct SubmissionCounts {
+    latest_slot: Slot,
+    counts: HashMap<(Slot, PublicKeyBytes), u32>,
+}
+
+impl SubmissionRateLimiter {
+    /// Counts a submission, returning `false` if the builder has exhausted its quota for the slot.
+    ///
+    /// Counts are forgotten once they fall too far behind the latest slot seen, so a
+    /// submission for a later slot never resets the quota of the current one. Callers reject
+    /// slots ahead of the wall clock first, see [`check_submission_slot`].
+    fn allow(&self, bid_trace: &BidTraceV1, max_submissions_per_slot: u32) -> bool {
+        let mut counts = self.counts.lock().unwrap();
+        if bid_trace.slot > counts.latest_slot {
+            counts.latest_slot = bid_trace.slot;
+            let oldest_slot = bid_trace.slot.saturating_sub(DEDUPLICATION_SLOTS);
+            counts.counts.retain(|(slot, _), _| *slot >= oldest_slot);
+        }
+
+        let count = counts
+            .counts
+            .entry((bid_trace.slot, bid_trace.builder_pubkey))
+            .or_default();
+        if *count >= max_submissions_per_slot {
+            return false;
+        }
+        *count += 1;
+        true
+    }
+}
+
+/// Number of slots past the current slot for which submissions are accepted, allowing for
+/// builders submitting just before the slot boundary.
+const MAX_SUBMISSION_SLOTS_AHEAD: u64 = 1;
+
+/// Returns a `400 Bad Request` response if the submission is for a slot too far ahead of the
+/// wall clock. Checked before deduplication and rate limiting, which prune against the latest
+/// slot seen and would otherwise stop pruning after a single far-future submission.
+async fn check_submission_slot(
+    config: &ServerConfig,
+    bid_trace: &BidTraceV1,
+) -> Result<Option<Response<Body>>, StatusCode> {
+    let current_slot = config.slot_clock.now();
+    if bid_trace.slot <= current_slot + MAX_SUBMISSION_SLOTS_AHEAD {
+        return Ok(None);
+    }
+
+    build_response(Err::<(), _>(ErrorResponse {
+        code: StatusCode::BAD_REQUEST.as_u16(),
+        message: format!(
+            "slot {} is ahead of the current slot {}",
+            bid_trace.slot, current_slot
+        ),
+        stacktraces: None,
+    }))
+    .await
+    .map(Some)
+}
+
+/// Returns a `429 Too Many Requests` response if the submission exceeds the configured quota.
+async fn check_rate_limit(
+    config: &ServerConfig,
+    limiter: &SubmissionRateLimiter,
+    bid_trace: &BidTraceV1,
+) -> Result<Option<Response<Body>>, StatusCode> {
+    let Some(rate_limit) = &config.rate_limit else {
+        return Ok(None);
+    };
+    if limiter.allow(bid_trace, rate_limit.max_submissions_per_slot) {
+        return Ok(None);
+    }
+
+    let mut response = build_response(Err::<(), _>(ErrorResponse {
+        code: StatusCode::TOO_MANY_REQUESTS.as_u16(),
+        message: format!(
+            "builder exceeded {} submissions for slot {}",
+            rate_limit.max_submissions_per_slot, bid_trace.slot
+        ),
+        stacktraces: None,
+    }))
+    .await?;
+    response.headers_mut().insert(
+        RETRY_AFTER,
+        HeaderValue::from(rate_limit.retry_after.as_secs()),
+    );
+    Ok(Some(response))
+}
+
+/// Awaits a submission's call into the API implementation, failing with `504 Gateway Timeout`
+/// once the configured submission timeout elapses. The request body has been fully decoded by
+/// then, so only the implementation's processing is cut short.
+async fn with_submission_timeout<T>(
+    config: &ServerConfig,
+    submission: impl Future<Output = Result<T, ErrorResponse>>,
+) -> Result<T, ErrorResponse> {
+    let Some(timeout) = config.submission_timeout else {
+        return submission.await;
+    };
+    tokio::time::timeout(timeout, submission)
+        .await
+        .unwrap_or_else(|_| {
+            Err(ErrorResponse {
+                code: StatusCode::GATEWAY_TIMEOUT.as_u16(),
+                message: format!("submission not processed within {}ms", timeout.as_millis()),
+                stacktraces: None,
+            })
+        })
+}
+
+/// Number of slots before the latest submission for which submissions are remembered.
+const DEDUPLICATION_SLOTS: u64 = 2;
+
+/// Recently processed block submissions, keyed by slot, builder and block hash.
+#[derive(Default)]
+struct SubmissionTracker {
+    seen: Mutex<SeenSubmissions>,
+}
+
+#[derive(Default)]
+struct SeenSubmissions {
+    latest_slot: Slot,
+    submissions: HashSet<(Slot, PublicKeyBytes, ExecutionBlockHash)>,
+}
+
+impl SubmissionTracker {
+    /// Returns `true` if the submission has already been processed.
+    fn contains(&self, bid_trace: &BidTraceV1) -> bool {
+        self.seen.lock().unwrap().submissions.contains(&(
+            bid_trace.slot,
+            bid_trace.builder_pubkey,
+            bid_trace.block_hash,
+        ))
+    }
+
+    /// Records a submission the API implementation has processed successfully. Submissions
+    /// are forgotten once they fall too far behind the latest slot seen. Callers reject slots
+    /// ahead of the wall clock first, see [`check_submission_slot`].
+    fn insert(&self, bid_trace: &BidTraceV1) {
+        let mut seen = self.seen.lock().unwrap();
+        seen.submissions.insert((
+            bid_trace.slot,
+            bid_trace.builder_pubkey,
+            bid_trace.block_hash,
+        ));
+        if bid_trace.slot > seen.latest_slot {
+            seen.latest_slot = bid_trace.slot;
+            let oldest_slot = bid_trace.slot.saturating_sub(DEDUPLICATION_SLOTS);
+            seen.submissions.retain(|(slot, _, _)| *slot >= oldest_slot);
+        }
+    }
+}
+
+/// Headers accepted by the optimistic v2 router whose payload hasn't been submitted yet, keyed
+/// by slot, builder and block hash.
+#[derive(Default)]
+struct PendingHeaders {
+    pending: Mutex<PendingHeaderSet>,
+}
+
+#[derive(Default)]
+struct PendingHeaderSet {
+    latest_slot: Slot,
+    headers: HashSet<(Slot, PublicKeyBytes, ExecutionBlockHash)>,
+}
+
+impl PendingHeaders {
+    /// Records an accepted header. Headers are forgotten once they fall too far behind the
+    /// latest slot seen, and headers already that far behind aren't recorded, so an earlier
+    /// slot never evicts the headers of the current one. Callers reject slots ahead of the wall
+    /// clock first, see [`check_submission_slot`].
+    fn insert(&self, bid_trace: &BidTraceV1) {
+        let mut pending = self.pending.lock().unwrap();
+        if bid_trace.slot > pending.latest_slot {
+            pending.latest_slot = bid_trace.slot;
+            let oldest_slot = bid_trace.slot.saturating_sub(DEDUPLICATION_SLOTS);
+            pending.headers.retain(|(slot, _, _)| *slot >= oldest_slot);
+        }
+        if bid_trace.slot >= pending.latest_slot.saturating_sub(DEDUPLICATION_SLOTS) {
+            pending.headers.insert((
+                bid_trace.slot,
+                bid_trace.builder_pubkey,
+                bid_trace.block_hash,
+            ));
+        }
+    }
+
+    /// Removes the header matching `bid_trace`, returning `false` if there is none.
+    fn remove(&self, bid_trace: &BidTraceV1) -> bool {
+        self.pending.lock().unwrap().headers.remove(&(
+            bid_trace.slot,
+            bid_trace.builder_pubkey,
+            bid_trace.block_hash,
+        ))
+    }
+}
 
 /// Setup API Server.
 pub fn new<I, A, E>(api_impl: I) -> Router
 where
     E: EthSpec,
     I: AsRef<A> + Clone + Send + Sync + 'static,
-    A: Builder<E> + Data + 'static,
+    A: Builder<E> + Data<E> + 'static,
 {
+    new_with_config(api_impl, ServerConfig::default())
+}
+
+/// Setup API Server with a custom [`ServerConfig`].
+pub fn new_with_config<I, A, E>(api_impl: I, config: ServerConfig) -> Router
+where
+    E: EthSpec,
+    I: AsRef<A> + Clone + Send + Sync + 'static,
+    A: Builder<E> + Data<E> + 'static,
+{
+    let config = config.clamped();
+
     // build our application with a route
     Router::new()
         .route("/relay/v1/builder/blocks", post(submit_block::<I, A, E>))
@@ -37,24 +362,72 @@ where
             "/relay/v1/builder/validators",
             get(get_validators::<I, A, E>),
         )
-        .route("/relay/v1/builder/headers", post(submit_header::<I, A, E>))
+        .route(
+            "/relay/v1/builder/capabilities",
+            get(get_capabilities::<I, A, E>),
+        )
         .route(
             "/relay/v1/builder/cancel_bid",
             post(submit_cancellation::<I, A, E>),
         )
         .route("/relay/v1/builder/top_bids", get(get_top_bids::<I, A, E>))
+        .route(
+            "/relay/v1/builder/top_bids/sse",
+            get(get_top_bids_sse::<I, A, E>),
+        )
         .route(
             "/relay/v1/data/bidtraces/builder_blocks_received",
-            get(get_received_bids::<I, A>),
+            get(get_received_bids::<I, A, E>),
         )
         .route(
             "/relay/v1/data/bidtraces/proposer_payload_delivered",
-            get(get_delivered_payloads::<I, A>),
+            get(get_delivered_payloads::<I, A, E>),
         )
         .route(
             "/relay/v1/data/validator_registration",
-            get(get_validator_registration::<I, A>),
+            get(get_validator_registration::<I, A, E>),
+        )
+        .route("/relay/v1/data/blobs", get(get_blobs_bundle::<I, A, E>))
+        .fallback(not_found)
+        .layer(middleware::map_response(method_not_allowed))
+        .layer(Extension(config))
+        .layer(Extension(Arc::new(SubmissionTracker::default())))
+        .layer(Extension(Arc::new(SubmissionRateLimiter::default())))
+        .with_state(api_impl)
+}
+
+/// Setup a router serving only the optimistic v2 header and payload submission endpoints,
+/// for relays deploying the optimistic path separately from the rest of the API.
+pub fn new_optimistic_v2<I, A, E>(api_impl: I) -> Router
+where
+    E: EthSpec,
+    I: AsRef<A> + Clone + Send + Sync + 'static,
+    A: OptimisticV2<E> + 'static,
+{
+    new_optimistic_v2_with_config(api_impl, ServerConfig::default())
+}
+
+/// Setup the optimistic v2 router with a custom [`ServerConfig`].
+pub fn new_optimistic_v2_with_config<I, A, E>(api_impl: I, config: ServerConfig) -> Router
+where
+    E: EthSpec,
+    I: AsRef<A> + Clone + Send + Sync + 'static,
+    A: OptimisticV2<E> + 'static,
+{
+    Router::new()
+        .route(
+            "/relay/v1/builder/headers",
+            post(submit_header_v2::<I, A, E>),
         )
+        .route(
+            "/relay/v1/builder/payloads_optimistic_v2",
+            post(submit_payload_v2::<I, A, E>),
+        )
+        .fallback(not_found)
+        .layer(middleware::map_response(method_not_allowed))
+        .layer(Extension(config))
+        .layer(Extension(Arc::new(SubmissionRateLimiter::default())))
+        .layer(Extension(Arc::new(PendingHeaders::default())))
         .with_state(api_impl)
 }
 
@@ -62,6 +435,9 @@ where
 #[tracing::instrument(skip_all)]
 async fn submit_block<I, A, E>(
     Query(query_params): Query<SubmitBlockQueryParams>,
+    Extension(config): Extension<ServerConfig>,
+    Extension(tracker): Extension<Arc<SubmissionTracker>>,
+    Extension(limiter): Extension<Arc<SubmissionRateLimiter>>,
     State(api_impl): State<I>,
     JsonOrSszMaybeGzipped(body): JsonOrSszMaybeGzipped<SubmitBlockRequest<E>>,
 ) -> Result<Response<Body>, StatusCode>
@@ -70,7 +446,33 @@ where
     I: AsRef<A> + Send + Sync,
     A: Builder<E>,
 {
-    let result = api_impl.as_ref().submit_block(query_params, body).await;
+    if let Some(response) = check_submission_slot(&config, body.message()).await? {
+        return Ok(response);
+    }
+    // Duplicates are acknowledged before the rate limit so retries don't use up the quota.
+    if config.deduplicate_submissions && tracker.contains(body.message()) {
+        return build_response(Ok(SubmitBlockResponse::duplicate())).await;
+    }
+    if let Some(response) = check_rate_limit(&config, &limiter, body.message()).await? {
+        return Ok(response);
+    }
+    if let Err(e) = body.validate_against_payload() {
+        return build_response(Err::<(), _>(ErrorResponse {
+            code: StatusCode::BAD_REQUEST.as_u16(),
+            message: e.to_string(),
+            stacktraces: None,
+        }))
+        .await;
+    }
+
+    let bid_trace = body.message().clone();
+
+    let result =
+        with_submission_timeout(&config, api_impl.as_ref().submit_block(query_params, body)).await;
+    // Only processed submissions are recorded, so a retry after a failure is passed through.
+    if config.deduplicate_submissions && result.is_ok() {
+        tracker.insert(&bid_trace);
+    }
     build_response(result).await
 }
 
@@ -78,6 +480,9 @@ where
 #[tracing::instrument(skip_all)]
 async fn submit_block_optimistic_v2<I, A, E>(
     Query(query_params): Query<SubmitBlockQueryParams>,
+    Extension(config): Extension<ServerConfig>,
+    Extension(tracker): Extension<Arc<SubmissionTracker>>,
+    Extension(limiter): Extension<Arc<SubmissionRateLimiter>>,
     State(api_impl): State<I>,
     JsonOrSszMaybeGzipped(body): JsonOrSszMaybeGzipped<SubmitBlockRequest<E>>,
 ) -> Result<Response<Body>, StatusCode>
@@ -86,10 +491,37 @@ where
     I: AsRef<A> + Send + Sync,
     A: Builder<E>,
 {
-    let result = api_impl
-        .as_ref()
-        .submit_block_optimistic_v2(query_params, body)
+    if let Some(response) = check_submission_slot(&config, body.message()).await? {
+        return Ok(response);
+    }
+    // Duplicates are acknowledged before the rate limit so retries don't use up the quota.
+    if config.deduplicate_submissions && tracker.contains(body.message()) {
+        return build_response(Ok(SubmitBlockResponse::duplicate())).await;
+    }
+    if let Some(response) = check_rate_limit(&config, &limiter, body.message()).await? {
+        return Ok(response);
+    }
+    if let Err(e) = body.validate_against_payload() {
+        return build_response(Err::<(), _>(ErrorResponse {
+            code: StatusCode::BAD_REQUEST.as_u16(),
+            message: e.to_string(),
+            stacktraces: None,
+        }))
         .await;
+    }
+
+    let bid_trace = body.message().clone();
+
+    let result = with_submission_timeout(
+        &config,
+        api_impl
+            .as_ref()
+            .submit_block_optimistic_v2(query_params, body),
+    )
+    .await;
+    if config.deduplicate_submissions && result.is_ok() {
+        tracker.insert(&bid_trace);
+    }
     build_response(result).await
 }
 
@@ -97,15 +529,102 @@ where
 #[tracing::instrument(skip_all)]
 async fn submit_header<I, A, E>(
     Query(query_params): Query<SubmitBlockQueryParams>,
+    Extension(config): Extension<ServerConfig>,
+    Extension(limiter): Extension<Arc<SubmissionRateLimiter>>,
     State(api_impl): State<I>,
-    JsonOrSszMaybeGzipped(body): JsonOrSszMaybeGzipped<SignedHeaderSubmission<E>>,
+    JsonOrSszWithFork(body): JsonOrSszWithFork<SignedHeaderSubmission<E>>,
 ) -> Result<Response<Body>, StatusCode>
 where
     E: EthSpec,
     I: AsRef<A> + Send + Sync,
     A: Builder<E>,
 {
-    let result = api_impl.as_ref().submit_header(query_params, body).await;
+    if let Some(response) = check_submission_slot(&config, body.bid_trace()).await? {
+        return Ok(response);
+    }
+    if let Some(response) = check_rate_limit(&config, &limiter, body.bid_trace()).await? {
+        return Ok(response);
+    }
+
+    let result =
+        with_submission_timeout(&config, api_impl.as_ref().submit_header(query_params, body)).await;
+    build_response(result).await
+}
+
+/// SubmitHeader - POST /relay/v1/builder/headers, served by the optimistic v2 router
+#[tracing::instrument(skip_all)]
+async fn submit_header_v2<I, A, E>(
+    Query(query_params): Query<SubmitBlockQueryParams>,
+    Extension(config): Extension<ServerConfig>,
+    Extension(limiter): Extension<Arc<SubmissionRateLimiter>>,
+    Extension(pending_headers): Extension<Arc<PendingHeaders>>,
+    State(api_impl): State<I>,
+    JsonOrSszWithFork(body): JsonOrSszWithFork<SignedHeaderSubmission<E>>,
+) -> Result<Response<Body>, StatusCode>
+where
+    E: EthSpec,
+    I: AsRef<A> + Send + Sync,
+    A: OptimisticV2<E>,
+{
+    if let Some(response) = check_submission_slot(&config, body.bid_trace()).await? {
+        return Ok(response);
+    }
+    if let Some(response) = check_rate_limit(&config, &limiter, body.bid_trace()).await? {
+        return Ok(response);
+    }
+
+    let bid_trace = body.bid_trace().clone();
+    let result = with_submission_timeout(
+        &config,
+        api_impl.as_ref().submit_header_v2(query_params, body),
+    )
+    .await;
+    if result.is_ok() {
+        pending_headers.insert(&bid_trace);
+    }
+    build_response(result).await
+}
+
+/// SubmitPayloadV2 - POST /relay/v1/builder/payloads_optimistic_v2
+#[tracing::instrument(skip_all)]
+async fn submit_payload_v2<I, A, E>(
+    Query(query_params): Query<SubmitBlockQueryParams>,
+    Extension(config): Extension<ServerConfig>,
+    Extension(pending_headers): Extension<Arc<PendingHeaders>>,
+    State(api_impl): State<I>,
+    JsonOrSszMaybeGzipped(body): JsonOrSszMaybeGzipped<SubmitBlockRequest<E>>,
+) -> Result<Response<Body>, StatusCode>
+where
+    E: EthSpec,
+    I: AsRef<A> + Send + Sync,
+    A: OptimisticV2<E>,
+{
+    if let Err(e) = body.validate_against_payload() {
+        return build_response(Err::<(), _>(ErrorResponse {
+            code: StatusCode::BAD_REQUEST.as_u16(),
+            message: e.to_string(),
+            stacktraces: None,
+        }))
+        .await;
+    }
+    if !pending_headers.remove(body.message()) {
+        let bid_trace = body.message();
+        return build_response(Err::<(), _>(ErrorResponse {
+            code: StatusCode::NOT_FOUND.as_u16(),
+            message: format!(
+                "no pending header for slot {} with block hash {}",
+                bid_trace.slot, bid_trace.block_hash
+            ),
+            stacktraces: None,
+        }))
+        .await;
+    }
+
+    let result = with_submission_timeout(
+        &config,
+        api_impl.as_ref().submit_payload_v2(query_params, body),
+    )
+    .await;
     build_response(result).await
 }
 
@@ -136,11 +655,25 @@ where
     build_response(result).await
 }
 
+/// GetCapabilities - GET /relay/v1/builder/capabilities
+#[tracing::instrument(skip_all)]
+async fn get_capabilities<I, A, E>(State(api_impl): State<I>) -> Result<Response<Body>, StatusCode>
+where
+    I: AsRef<A> + Send + Sync,
+    A: Builder<E>,
+    E: EthSpec,
+{
+    let result = api_impl.as_ref().get_capabilities().await;
+    build_response(result).await
+}
+
 /// GetTopBids - GET /relay/v1/builder/top_bids
 #[tracing::instrument(skip_all)]
 async fn get_top_bids<I, A, E>(
     ws: WebSocketUpgrade,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Query(query_params): Query<TopBidsQueryParams>,
+    Extension(config): Extension<ServerConfig>,
     State(api_impl): State<I>,
 ) -> impl IntoResponse
 where
@@ -148,50 +681,127 @@ where
     A: Builder<E> + 'static,
     E: EthSpec,
 {
-    ws.on_upgrade(move |socket| handle_socket(socket, addr, api_impl))
+    ws.on_upgrade(move |socket| handle_socket(socket, addr, query_params, config, api_impl))
 }
 
-async fn handle_socket<I, A, E>(socket: WebSocket, who: SocketAddr, api_impl: I)
+/// GetTopBids as server-sent events - GET /relay/v1/builder/top_bids/sse
+///
+/// For clients that can't use websockets. Each update is sent as a JSON `data` event, with
+/// keep-alive comments sent at the websocket ping interval.
+#[tracing::instrument(skip_all)]
+async fn get_top_bids_sse<I, A, E>(
+    Extension(config): Extension<ServerConfig>,
+    State(api_impl): State<I>,
+) -> Result<Response<Body>, StatusCode>
 where
+    I: AsRef<A> + Send + Sync,
+    A: Builder<E>,
+    E: EthSpec,
+{
+    let stream = match api_impl.as_ref().get_top_bids().await {
+        Ok(stream) => stream,
+        Err(e) => return build_response(Err::<(), _>(e)).await,
+    };
+
+    let events = stream.filter_map(|update| async move {
+        match Event::default().json_data(&update) {
+            Ok(event) => Some(Ok::<_, Infallible>(event)),
+            Err(e) => {
+                tracing::error!("Error serializing update: {:?}", e);
+                None
+            }
+        }
+    });
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::new().interval(config.ws_ping_interval))
+        .into_response())
+}
+
+async fn handle_socket<I, A, E>(
+    socket: WebSocket,
+    who: SocketAddr,
+    query_params: TopBidsQueryParams,
+    config: ServerConfig,
+    api_impl: I,
+) where
     I: AsRef<A> + Send + Sync + 'static,
     A: Builder<E>,
     E: EthSpec,
 {
     let (mut sender, mut receiver) = socket.split();
+    let format = query_params.format.unwrap_or_default();
 
-    let mut send_task = tokio::spawn(async move {
-        let stream = match api_impl.as_ref().get_top_bids().await {
+    // Updates are buffered in a bounded channel that drops the oldest entries when a slow
+    // subscriber falls behind, so the source stream is never held up by the socket.
+    let (update_tx, mut update_rx) = broadcast::channel(config.ws_buffer_size);
+    let producer_task = tokio::spawn(async move {
+        let mut stream = match api_impl.as_ref().get_top_bids().await {
             Ok(stream) => stream,
             Err(e) => {
                 tracing::error!("Failed to get top bids stream: {:?}", e);
-                let _ = sender.close().await;
                 return;
             }
         };
 
-        let mut stream = stream;
+        // Filtering before the buffer keeps unwanted updates from pushing out wanted ones.
+        let mut stream = stream.filter(|update| std::future::ready(query_params.matches(update)));
         while let Some(update) = stream.next().await {
-            match serde_json::to_string(&update) {
-                Ok(json) => {
-                    if let Err(e) = sender.send(Message::Text(json)).await {
-                        tracing::error!("Error sending message: {:?}", e);
+            if update_tx.send(update).is_err() {
+                break;
+            }
+        }
+    });
+
+    let ping_interval = config.ws_ping_interval;
+    let mut send_task = tokio::spawn(async move {
+        let mut ping_interval = tokio::time::interval(ping_interval);
+        // The first tick completes immediately.
+        ping_interval.tick().await;
+        loop {
+            tokio::select! {
+                _ = ping_interval.tick() => {
+                    if let Err(e) = sender.send(Message::Ping(Vec::new())).await {
+                        tracing::error!("Error sending ping: {:?}", e);
                         break;
                     }
                 }
-                Err(e) => {
-                    tracing::error!("Error serializing update: {:?}", e);
-                    continue;
+                update = update_rx.recv() => {
+                    let update = match update {
+                        Ok(update) => update,
+                        Err(RecvError::Lagged(skipped)) => {
+                            tracing::warn!("Client {} is lagging, dropped {} top bid updates", who, skipped);
+                            continue;
+                        }
+                        Err(RecvError::Closed) => break,
+                    };
+                    let message = match encode_top_bid(&update, format) {
+                        Ok(message) => message,
+                        Err(e) => {
+                            tracing::error!("Error serializing update: {:?}", e);
+                            continue;
+                        }
+                    };
+                    if let Err(e) = sender.send(message).await {
+                        tracing::error!("Error sending message: {:?}", e);
+                        break;
+                    }
                 }
             }
         }
         let _ = sender.close().await;
     });
 
+    // Any message, including pongs and client pings, counts as liveness.
+    let idle_timeout = config.ws_ping_interval + config.ws_pong_timeout;
     let mut recv_task = tokio::spawn(async move {
-        while let Some(Ok(message)) = receiver.next().await {
-            match message {
-                Message::Close(_) => break,
-                _ => {}
+        loop {
+            match tokio::time::timeout(idle_timeout, receiver.next()).await {
+                Ok(Some(Ok(Message::Close(_)))) | Ok(Some(Err(_))) | Ok(None) => break,
+                Ok(Some(Ok(_))) => {}
+                Err(_) => {
+                    tracing::info!("Client {} did not respond to ping", who);
+                    break;
+                }
             }
         }
     });
@@ -200,46 +810,84 @@ where
         _ = (&mut send_task) => recv_task.abort(),
         _ = (&mut recv_task) => send_task.abort(),
     }
+    producer_task.abort();
 
     tracing::info!("Client {} disconnected", who);
 }
+
+fn encode_top_bid(
+    update: &TopBidUpdate,
+    format: TopBidsFormat,
+) -> Result<Message, serde_json::Error> {
+    match format {
+        TopBidsFormat::Json => serde_json::to_string(update).map(Message::Text),
+        TopBidsFormat::Ssz => Ok(Message::Binary(update.as_ssz_bytes())),
+    }
+}
+
+/// Caps a data request `limit` at `max_limit`, defaulting to `max_limit` when unset.
+fn clamp_limit(limit: Option<Slot>, max_limit: u64) -> Slot {
+    let max_limit = Slot::new(max_limit);
+    limit.map_or(max_limit, |limit| limit.min(max_limit))
+}
+
 /// GetDeliveredPayloads - GET /relay/v1/data/bidtraces/proposer_payload_delivered
 #[tracing::instrument(skip_all)]
-async fn get_delivered_payloads<I, A>(
-    Query(query_params): Query<GetDeliveredPayloadsQueryParams>,
+async fn get_delivered_payloads<I, A, E>(
+    Query(mut query_params): Query<GetDeliveredPayloadsQueryParams>,
+    headers: HeaderMap,
+    Extension(config): Extension<ServerConfig>,
     State(api_impl): State<I>,
 ) -> Result<Response<Body>, StatusCode>
 where
     I: AsRef<A> + Send + Sync,
-    A: Data,
+    A: Data<E>,
+    E: EthSpec,
 {
+    if let Err(e) = query_params.validate() {
+        return build_response(Err::<(), _>(ErrorResponse {
+            code: StatusCode::BAD_REQUEST.as_u16(),
+            message: e.to_string(),
+            stacktraces: None,
+        }))
+        .await;
+    }
+
+    query_params.limit = Some(clamp_limit(query_params.limit, config.data_max_limit));
+    let content_type = data_content_type(&headers)?;
     let result = api_impl.as_ref().get_delivered_payloads(query_params).await;
-    build_response(result).await
+    build_cached_response(result, content_type, &headers, config.data_cache_max_age).await
 }
 
 /// GetReceivedBids - GET /relay/v1/data/bidtraces/builder_blocks_received
 #[tracing::instrument(skip_all)]
-async fn get_received_bids<I, A>(
-    Query(query_params): Query<GetReceivedBidsQueryParams>,
+async fn get_received_bids<I, A, E>(
+    Query(mut query_params): Query<GetReceivedBidsQueryParams>,
+    headers: HeaderMap,
+    Extension(config): Extension<ServerConfig>,
     State(api_impl): State<I>,
 ) -> Result<Response<Body>, StatusCode>
 where
     I: AsRef<A> + Send + Sync,
-    A: Data,
+    A: Data<E>,
+    E: EthSpec,
 {
+    query_params.limit = Some(clamp_limit(query_params.limit, config.data_max_limit));
+    let content_type = data_content_type(&headers)?;
     let result = api_impl.as_ref().get_received_bids(query_params).await;
-    build_response(result).await
+    build_cached_response(result, content_type, &headers, config.data_cache_max_age).await
 }
 
 /// GetValidatorRegistration - GET /relay/v1/data/validator_registration
 #[tracing::instrument(skip_all)]
-async fn get_validator_registration<I, A>(
+async fn get_validator_registration<I, A, E>(
     Query(query_params): Query<GetValidatorRegistrationQueryParams>,
     State(api_impl): State<I>,
 ) -> Result<Response<Body>, StatusCode>
 where
     I: AsRef<A> + Send + Sync,
-    A: Data,
+    A: Data<E>,
+    E: EthSpec,
 {
     let result = api_impl
         .as_ref()
@@ -247,3 +895,94 @@ where
         .await;
     build_response(result).await
 }
+
+/// GetBlobsBundle - GET /relay/v1/data/blobs
+#[tracing::instrument(skip_all)]
+async fn get_blobs_bundle<I, A, E>(
+    Query(query_params): Query<GetBlobsBundleQueryParams>,
+    State(api_impl): State<I>,
+) -> Result<Response<Body>, StatusCode>
+where
+    I: AsRef<A> + Send + Sync,
+    A: Data<E>,
+    E: EthSpec,
+{
+    let result = api_impl.as_ref().get_blobs_bundle(query_params).await;
+    build_response(result).await
+}
+
+/// Picks the bid trace data response encoding from the request's `Accept` header.
+fn data_content_type(headers: &HeaderMap) -> Result<ContentType, StatusCode> {
+    let accept = headers
+        .get(ACCEPT)
+        .map(|value| value.to_str().unwrap_or_default());
+    negotiate_content_type(accept, &[ContentType::Json, ContentType::Ssz])
+        .ok_or(StatusCode::NOT_ACCEPTABLE)
+}
+
+/// Builds a JSON or SSZ response with `Cache-Control` and `ETag` headers, answering with
+/// `304 Not Modified` when the request's `If-None-Match` matches the body. Errors are always
+/// returned as JSON.
+async fn build_cached_response<T>(
+    result: Result<T, ErrorResponse>,
+    content_type: ContentType,
+    request_headers: &HeaderMap,
+    max_age: Duration,
+) -> Result<Response<Body>, StatusCode>
+where
+    T: Serialize + Encode + Send + 'static,
+{
+    let response = match content_type {
+        ContentType::Ssz => build_unversioned_ssz_response(result).await?,
+        ContentType::Json => build_response(result).await?,
+    };
+    if response.status() != StatusCode::OK {
+        return Ok(response);
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = axum::body::to_bytes(body, usize::MAX).await.map_err(|e| {
+        tracing::error!(error = ?e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // A SHA-256 digest rather than `DefaultHasher`, whose output may change between Rust
+    // releases, so replicas built with different toolchains agree on the tag.
+    let digest = Sha256::digest(&body);
+    let tag: String = digest[..16]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    let etag = HeaderValue::from_str(&format!("\"{}\"", tag)).map_err(|e| {
+        tracing::error!(error = ?e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let cache_control = HeaderValue::from_str(&format!("public, max-age={}", max_age.as_secs()))
+        .map_err(|e| {
+            tracing::error!(error = ?e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let not_modified = request_headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|tag| {
+            // `If-None-Match` uses the weak comparison, so a weak validator for the tag matches.
+            let tag = tag.trim();
+            tag == "*" || etag == tag.strip_prefix("W/").unwrap_or(tag)
+        });
+
+    parts.headers.insert(ETAG, etag);
+    parts.headers.insert(CACHE_CONTROL, cache_control);
+    parts
+        .headers
+        .insert(VARY, HeaderValue::from_static("Accept"));
+    if not_modified {
+        parts.status = StatusCode::NOT_MODIFIED;
+        parts.headers.remove(CONTENT_TYPE);
+        return Ok(Response::from_parts(parts, Body::empty()));
+    }
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
@@ -0,0 +1,306 @@
+use std::io::Write;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::body::{to_bytes, Body};
+use axum::Router;
+use ethereum_apis_common::ErrorResponse;
+use flate2::{write::GzEncoder, Compression};
+use http::{
+    header::{CONTENT_ENCODING, CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+    Request, StatusCode,
+};
+use relay_server::mock::MockRelay;
+use relay_server::server::{ServerConfig, SlotClock, SubmissionRateLimit};
+use relay_server::{
+    Address, BidTraceV1, Blob, BlobsBundle, ExecutionBlockHash, ExecutionPayloadDeneb,
+    ExecutionPayloadElectra, Hash256, KzgCommitment, KzgProof, MainnetEthSpec, PublicKeyBytes,
+    Signature, Slot, SubmitBlockRequest, SubmitBlockRequestDeneb, SubmitBlockRequestElectra,
+    SubmitBlockResponse, Uint256,
+};
+use ssz::Encode;
+use tower::ServiceExt;
+
+type E = MainnetEthSpec;
+
+fn router(relay: Arc<MockRelay<E>>) -> Router {
+    relay_server::server::new::<_, MockRelay<E>, E>(relay)
+}
+
+/// Returns a clock whose current slot is `slot`.
+fn clock_at(slot: u64) -> SlotClock {
+    let slot_duration = Duration::from_secs(12);
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    SlotClock {
+        genesis_time: now - slot_duration * slot as u32,
+        slot_duration,
+    }
+}
+
+async fn submit_block(router: &Router, submission: &SubmitBlockRequest<E>) -> StatusCode {
+    router
+        .clone()
+        .oneshot(
+            Request::post("/relay/v1/builder/blocks")
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_vec(submission).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+        .status()
+}
+
+fn deneb_submission(slot: Slot, blobs_bundle: BlobsBundle<E>) -> SubmitBlockRequest<E> {
+    let execution_payload = ExecutionPayloadDeneb::<E> {
+        parent_hash: ExecutionBlockHash::from_root(Hash256::repeat_byte(1)),
+        block_hash: ExecutionBlockHash::from_root(Hash256::repeat_byte(2)),
+        gas_limit: 30_000_000,
+        gas_used: 15_000_000,
+        ..Default::default()
+    };
+    let message = BidTraceV1 {
+        slot,
+        parent_hash: execution_payload.parent_hash,
+        block_hash: execution_payload.block_hash,
+        builder_pubkey: PublicKeyBytes::empty(),
+        proposer_pubkey: PublicKeyBytes::empty(),
+        proposer_fee_recipient: Address::repeat_byte(3),
+        gas_limit: execution_payload.gas_limit,
+        gas_used: execution_payload.gas_used,
+        value: Uint256::from(1_000_000_000u64),
+    };
+    SubmitBlockRequest::Deneb(SubmitBlockRequestDeneb {
+        message,
+        execution_payload,
+        signature: Signature::empty(),
+        blobs_bundle,
+    })
+}
+
+fn electra_submission(slot: Slot, blobs_bundle: BlobsBundle<E>) -> SubmitBlockRequest<E> {
+    let SubmitBlockRequest::Deneb(deneb) = deneb_submission(slot, blobs_bundle) else {
+        unreachable!("deneb_submission builds a Deneb submission");
+    };
+    let execution_payload = ExecutionPayloadElectra::<E> {
+        parent_hash: deneb.execution_payload.parent_hash,
+        block_hash: deneb.execution_payload.block_hash,
+        gas_limit: deneb.execution_payload.gas_limit,
+        gas_used: deneb.execution_payload.gas_used,
+        ..Default::default()
+    };
+    SubmitBlockRequest::Electra(SubmitBlockRequestElectra {
+        message: deneb.message,
+        execution_payload,
+        signature: deneb.signature,
+        blobs_bundle: deneb.blobs_bundle,
+        execution_requests: Default::default(),
+    })
+}
+
+fn blobs_query(slot: Slot, block_hash: ExecutionBlockHash) -> String {
+    let block_hash = serde_json::to_value(block_hash).unwrap();
+    format!(
+        "/relay/v1/data/blobs?slot={}&block_hash={}",
+        slot,
+        block_hash.as_str().unwrap()
+    )
+}
+
+#[tokio::test]
+async fn blobs_bundle_round_trips_from_submissions_with_blobs() {
+    for build_submission in [deneb_submission, electra_submission] {
+        let relay = Arc::new(MockRelay::<E>::new());
+        let blobs_bundle = BlobsBundle::<E> {
+            commitments: vec![KzgCommitment::empty_for_testing()].into(),
+            proofs: vec![KzgProof::empty()].into(),
+            blobs: vec![Blob::<E>::default()].into(),
+        };
+        let submission = build_submission(Slot::new(1), blobs_bundle.clone());
+        let bid_trace = submission.message().clone();
+
+        let response = router(relay.clone())
+            .oneshot(
+                Request::post("/relay/v1/builder/blocks")
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(Body::from(serde_json::to_vec(&submission).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = router(relay.clone())
+            .oneshot(
+                Request::get(blobs_query(bid_trace.slot, bid_trace.block_hash))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let served: BlobsBundle<E> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(served, blobs_bundle);
+
+        let response = router(relay)
+            .oneshot(
+                Request::get(blobs_query(bid_trace.slot + 1, bid_trace.block_hash))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
+
+#[tokio::test]
+async fn submit_block_accepts_gzipped_ssz() {
+    let relay = Arc::new(MockRelay::<E>::new());
+    let submission = deneb_submission(Slot::new(1), BlobsBundle::default());
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&submission.as_ssz_bytes()).unwrap();
+    let body = encoder.finish().unwrap();
+
+    let response = router(relay.clone())
+        .oneshot(
+            Request::post("/relay/v1/builder/blocks")
+                .header(CONTENT_TYPE, "application/octet-stream")
+                .header(CONTENT_ENCODING, "gzip")
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let blocks = relay.blocks();
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].message(), submission.message());
+}
+
+#[tokio::test]
+async fn submit_block_rejects_invalid_gzip() {
+    let relay = Arc::new(MockRelay::<E>::new());
+    let submission = deneb_submission(Slot::new(1), BlobsBundle::default());
+
+    let response = router(relay.clone())
+        .oneshot(
+            Request::post("/relay/v1/builder/blocks")
+                .header(CONTENT_TYPE, "application/octet-stream")
+                .header(CONTENT_ENCODING, "gzip")
+                .body(Body::from(submission.as_ssz_bytes()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(relay.blocks().is_empty());
+}
+
+#[tokio::test]
+async fn far_future_slot_neither_resets_quota_nor_disables_pruning() {
+    let relay = Arc::new(MockRelay::<E>::new());
+    let config = ServerConfig {
+        rate_limit: Some(SubmissionRateLimit {
+            max_submissions_per_slot: 1,
+            retry_after: Duration::from_secs(1),
+        }),
+        slot_clock: clock_at(100),
+        ..Default::default()
+    };
+    let router = relay_server::server::new_with_config::<_, MockRelay<E>, E>(relay, config);
+    let at_slot = |slot| deneb_submission(Slot::new(slot), BlobsBundle::default());
+
+    assert_eq!(submit_block(&router, &at_slot(97)).await, StatusCode::OK);
+    assert_eq!(
+        submit_block(&router, &at_slot(97)).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    assert_eq!(submit_block(&router, &at_slot(100)).await, StatusCode::OK);
+
+    assert_eq!(
+        submit_block(&router, &at_slot(u64::MAX)).await,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        submit_block(&router, &at_slot(100)).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    // Advancing to the next slot still prunes the counts of old slots.
+    assert_eq!(submit_block(&router, &at_slot(101)).await, StatusCode::OK);
+    assert_eq!(submit_block(&router, &at_slot(97)).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn duplicate_submission_acknowledged_after_success_only() {
+    let relay = Arc::new(MockRelay::<E>::new());
+    let config = ServerConfig {
+        deduplicate_submissions: true,
+        ..Default::default()
+    };
+    let router = relay_server::server::new_with_config::<_, MockRelay<E>, E>(relay.clone(), config);
+    let submission = deneb_submission(Slot::new(1), BlobsBundle::default());
+    let submit = || {
+        router.clone().oneshot(
+            Request::post("/relay/v1/builder/blocks")
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_vec(&submission).unwrap()))
+                .unwrap(),
+        )
+    };
+    let is_duplicate = |body: &[u8]| {
+        serde_json::from_slice::<SubmitBlockResponse>(body)
+            .map(|response| response.is_duplicate())
+            .unwrap_or(false)
+    };
+
+    relay.fail_next_submission(ErrorResponse {
+        code: 500,
+        message: "relay unavailable".to_string(),
+        stacktraces: None,
+    });
+    let response = submit().await.unwrap();
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(relay.blocks().is_empty());
+
+    // The failed submission wasn't recorded, so its retry is passed through.
+    let response = submit().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(!is_duplicate(&body));
+    assert_eq!(relay.blocks().len(), 1);
+
+    let response = submit().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(is_duplicate(&body));
+    assert_eq!(relay.blocks().len(), 1);
+}
+
+#[tokio::test]
+async fn received_bids_not_modified_for_weak_etag() {
+    let relay = Arc::new(MockRelay::<E>::new());
+    let router = router(relay);
+    let get_bids = |if_none_match: Option<String>| {
+        let mut request = Request::get("/relay/v1/data/bidtraces/builder_blocks_received");
+        if let Some(if_none_match) = if_none_match {
+            request = request.header(IF_NONE_MATCH, if_none_match);
+        }
+        router.clone().oneshot(request.body(Body::empty()).unwrap())
+    };
+
+    let response = get_bids(None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = response.headers()[ETAG].to_str().unwrap().to_string();
+
+    let response = get_bids(Some(etag.clone())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    let response = get_bids(Some(format!("W/{}", etag))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    let response = get_bids(Some("W/\"stale\"".to_string())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}